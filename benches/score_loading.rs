@@ -0,0 +1,90 @@
+//! Benchmarks for loading long, high-resolution scores.
+//!
+//! Alongside the timings reported by criterion, the peak memory allocated while loading each score is printed.
+
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	hint::black_box,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use midi_file_performer::Performer;
+use midly::{num::{u15, u24, u28, u4, u7}, Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+
+struct CountingAllocator;
+
+static ALLOCATED:AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED:AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout:Layout) -> *mut u8 {
+		let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+		PEAK_ALLOCATED.fetch_max(allocated, Ordering::Relaxed);
+		unsafe { System.alloc(layout) }
+	}
+	unsafe fn dealloc(&self, pointer:*mut u8, layout:Layout) {
+		ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+		unsafe { System.dealloc(pointer, layout) }
+	}
+}
+
+#[global_allocator]
+static GLOBAL:CountingAllocator = CountingAllocator;
+
+/// Build a score of the given length (in minutes, at 120 BPM) and resolution, with a note on every eighth note
+/// in each of the given number of tracks.
+fn build_score(minutes:usize, ticks_per_beat:u16, track_count:usize) -> Smf<'static> {
+	let eighth_note = u32::from(ticks_per_beat / 2);
+	let note_count = minutes * 120 * 2;
+
+	let mut tracks = vec![];
+	for track_index in 0..track_count {
+		let mut track = vec![];
+		if track_index == 0 {
+			track.push(TrackEvent { delta: u28::new(0), kind: TrackEventKind::Meta(MetaMessage::Tempo(u24::new(500_000))) });
+		}
+		for note_index in 0..note_count {
+			let key = u7::new(48 + (note_index % 24) as u8);
+			let channel = u4::new(track_index as u8);
+			track.push(TrackEvent { delta: u28::new(0), kind: TrackEventKind::Midi { channel, message: MidiMessage::NoteOn { key, vel: u7::new(100) } } });
+			track.push(TrackEvent { delta: u28::new(eighth_note), kind: TrackEventKind::Midi { channel, message: MidiMessage::NoteOff { key, vel: u7::new(0) } } });
+		}
+		track.push(TrackEvent { delta: u28::new(0), kind: TrackEventKind::Meta(MetaMessage::EndOfTrack) });
+		tracks.push(track);
+	}
+
+	Smf {
+		header: Header::new(Format::Parallel, Timing::Metrical(u15::new(ticks_per_beat))),
+		tracks
+	}
+}
+
+fn score_loading(criterion:&mut Criterion) {
+	let mut group = criterion.benchmark_group("score_loading");
+	group.sample_size(10);
+
+	for (minutes, ticks_per_beat) in [(3, 96), (30, 960)] {
+		let standard_midi_file = build_score(minutes, ticks_per_beat, 8);
+		let description = format!("{minutes} minutes at {ticks_per_beat} PPQ");
+
+		//memory
+			let baseline = ALLOCATED.load(Ordering::Relaxed);
+			PEAK_ALLOCATED.store(baseline, Ordering::Relaxed);
+			let performer = Performer::try_new(standard_midi_file.clone()).unwrap();
+			let peak = PEAK_ALLOCATED.load(Ordering::Relaxed) - baseline;
+			let retained = ALLOCATED.load(Ordering::Relaxed) - baseline;
+			drop(performer);
+			println!("{description}: peak {} KiB allocated while loading, {} KiB retained", peak / 1024, retained / 1024);
+
+		//time
+			group.bench_with_input(BenchmarkId::from_parameter(&description), &standard_midi_file, |bencher, standard_midi_file| {
+				bencher.iter(|| Performer::try_new(black_box(standard_midi_file.clone())).unwrap());
+			});
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, score_loading);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+use std::{thread::sleep, time::Duration};
+
+use clap::Parser;
+
+use midi_file_performer::{MidirSink, Performer};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+#[command(arg_required_else_help(true))]
+struct Args {
+	/// List all connected midi output devices
+	#[arg(short, long, default_value_t = false)]
+	list: bool,
+
+	/// The name of the connected midi output device to use 
+	#[arg(short, long)]
+	device: Option<String>,
+
+	/// Ignore any connected midi output devices and set up a virtual output instead
+	#[arg(short, long, default_value_t = false)]
+	use_virtual: bool
+}
+
+fn main() {
+	let args = Args::parse();
+
+	//get midi output sink
+		if args.list {
+			let port_names = MidirSink::list_ports("play_midi").unwrap();
+			if port_names.is_empty() {
+				println!("there are no MIDI output devices detected");
+			} else {
+				port_names.iter().enumerate().for_each(|(index, port_name)| {
+					println!("{index} > {port_name}");
+				});
+			}
+
+			return;
+		}
+
+		let sink = match args.device {
+			Some(device) if !args.use_virtual => {
+				println!("using MIDI output device {device}");
+				MidirSink::connect("play_midi", &device).unwrap()
+			},
+			_ => virtual_sink()
+		};
+
+	//load midi file
+		let standard_midi_file = midly::Smf::parse(include_bytes!("../test_midi_files/scarborough_fair.mid")).unwrap();
+
+	//perform
+		let mut performer = Performer::new_with_sink(standard_midi_file, sink);
+		performer.play().ok();
+
+	//wait for the performance to finish
+		while performer.is_playing() {
+			if performer.poll().ok().unwrap().is_none() {
+				break;
+			}
+			sleep(Duration::from_millis(100));
+		}
+}
+
+#[cfg(unix)]
+fn virtual_sink() -> MidirSink {
+	println!("using virtual MIDI output midi_file_performer");
+	MidirSink::create_virtual("play_midi", "midi_file_performer").unwrap()
+}
+
+// (virtual ports are only available on unix platforms, so fall back to the first connected device)
+#[cfg(not(unix))]
+fn virtual_sink() -> MidirSink {
+	println!("virtual MIDI outputs are not supported on this platform, using the first MIDI output device instead");
+	MidirSink::connect_to_index("play_midi", 0).unwrap_or_else(|_| {
+		eprintln!("there are no MIDI output devices detected");
+		std::process::exit(1);
+	})
+}
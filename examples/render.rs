@@ -0,0 +1,16 @@
+use midi_file_performer::{Event, Performer};
+
+fn main() {
+	//load midi file
+		let standard_midi_file = midly::Smf::parse(include_bytes!("../test_midi_files/scarborough_fair.mid")).unwrap();
+
+	//render at double speed
+		let mut performer = Performer::new(standard_midi_file);
+		performer.set_speed(2.0).ok();
+
+		for (time, track, event) in performer.render().unwrap() {
+			if let Event::Midi(midi_event) = event {
+				println!("{time:>12.3?}  track {track}  {midi_event:?}");
+			}
+		}
+}
@@ -0,0 +1,15 @@
+use std::fs::File;
+
+use midi_file_performer::Performer;
+
+fn main() {
+	//load midi file
+		let standard_midi_file = midly::Smf::parse(include_bytes!("../test_midi_files/scarborough_fair.mid")).unwrap();
+
+	//render through the built-in synth
+		let performer = Performer::new(standard_midi_file);
+		let file = File::create("scarborough_fair.wav").unwrap();
+		performer.render_to_wav(file, 44_100, None).unwrap();
+
+		println!("rendered to scarborough_fair.wav");
+}
@@ -0,0 +1,33 @@
+use std::{fs::File, sync::Arc};
+
+use clap::Parser;
+
+use midi_file_performer::{Performer, SoundFont, Synth};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+#[command(arg_required_else_help(true))]
+struct Args {
+	/// The SoundFont (.sf2) file to play the score with
+	#[arg(short, long)]
+	sound_font: String,
+
+	/// The WAV file to write
+	#[arg(short, long, default_value = "scarborough_fair.wav")]
+	output: String
+}
+
+fn main() {
+	let args = Args::parse();
+
+	//load midi file and SoundFont
+		let standard_midi_file = midly::Smf::parse(include_bytes!("../test_midi_files/scarborough_fair.mid")).unwrap();
+		let sound_font = SoundFont::load(&args.sound_font).unwrap();
+
+	//render through the synth
+		let performer = Performer::new(standard_midi_file);
+		let mut synth = Synth::with_sound_font(44_100, Arc::new(sound_font)).unwrap();
+		performer.render_to_wav_with_synth(File::create(&args.output).unwrap(), &mut synth, None).unwrap();
+
+		println!("rendered to {}", args.output);
+}
@@ -0,0 +1,77 @@
+use std::{sync::{Arc, Mutex}, time::Instant};
+
+use crossbeam_channel::{Receiver, TryRecvError};
+
+use crate::{messages::ToEngine, sleep::{self, Wake}};
+
+/// The source of time for the engine, and the means by which it waits for it to pass.
+pub trait Clock: Send {
+	fn now(&self) -> Instant;
+	/// Block until a message arrives on the channel or the deadline (if any) is reached.
+	fn wait(&self, channel:&Receiver<ToEngine>, deadline:Option<Instant>) -> Wake<ToEngine>;
+}
+
+/// The real passage of time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+	fn wait(&self, channel:&Receiver<ToEngine>, deadline:Option<Instant>) -> Wake<ToEngine> {
+		sleep::wait(channel, deadline)
+	}
+}
+
+/// A clock whose time only moves when it is advanced, or when waiting for a deadline with no messages to attend to
+/// (in which case it jumps straight to the deadline).
+pub struct VirtualClock {
+	now: Mutex<Instant>
+}
+
+impl VirtualClock {
+	pub fn new(start:Instant) -> VirtualClock {
+		VirtualClock {
+			now: Mutex::new(start)
+		}
+	}
+
+	/// Move the clock on to the given instant. (The clock never moves backwards.)
+	pub fn advance_to(&self, instant:Instant) {
+		let mut now = self.now.lock().expect("the clock's lock is never held while panicking");
+		if instant > *now {
+			*now = instant;
+		}
+	}
+}
+
+impl Clock for VirtualClock {
+	fn now(&self) -> Instant {
+		*self.now.lock().expect("the clock's lock is never held while panicking")
+	}
+	fn wait(&self, channel:&Receiver<ToEngine>, deadline:Option<Instant>) -> Wake<ToEngine> {
+		match (channel.try_recv(), deadline) {
+			(Ok(message), _) => Wake::Message(message),
+			(Err(TryRecvError::Disconnected), _) => Wake::Disconnected,
+			(Err(TryRecvError::Empty), Some(deadline)) => {
+				self.advance_to(deadline);
+				Wake::Deadline
+			},
+			(Err(TryRecvError::Empty), None) => {
+				match channel.recv() {
+					Ok(message) => Wake::Message(message),
+					Err(_) => Wake::Disconnected,
+				}
+			},
+		}
+	}
+}
+
+impl<C: Clock + Sync + ?Sized> Clock for Arc<C> {
+	fn now(&self) -> Instant {
+		self.as_ref().now()
+	}
+	fn wait(&self, channel:&Receiver<ToEngine>, deadline:Option<Instant>) -> Wake<ToEngine> {
+		self.as_ref().wait(channel, deadline)
+	}
+}
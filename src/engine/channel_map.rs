@@ -0,0 +1,32 @@
+use midly::num::u4;
+
+/// Where the events of each channel of a score are sent, letting channels be moved (or merged) without editing the
+/// score. By default, each channel is sent to itself.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ChannelMap {
+	destinations: [u4; 16],
+}
+
+impl Default for ChannelMap {
+	fn default() -> ChannelMap {
+		ChannelMap {
+			destinations: std::array::from_fn(|channel| u4::new(channel as u8))
+		}
+	}
+}
+
+impl ChannelMap {
+	pub fn get(&self, channel:u4) -> u4 {
+		self.destinations[usize::from(u8::from(channel))]
+	}
+	pub fn set(&mut self, channel:u4, destination:u4) {
+		self.destinations[usize::from(u8::from(channel))] = destination;
+	}
+
+	/// The channels which have been sent somewhere other than themselves, along with where to.
+	pub fn get_remapped(&self) -> impl Iterator<Item = (u4, u4)> + '_ {
+		(0..16)
+			.map(|channel| (u4::new(channel), self.destinations[usize::from(channel)]))
+			.filter(|(channel, destination)| channel != destination)
+	}
+}
@@ -1,11 +1,15 @@
 use crossbeam_channel::SendError;
 
-use crate::ToConsole;
+use crate::{sink::SinkError, ToConsole};
 
 #[derive(Debug)]
 pub enum Error {
 	/// The timer could not be created due to the use of an incompatible timing value.
 	TimerCreation,
+	/// There were no scores to play.
+	EmptyPlaylist,
 	/// An issue related to the communication channels between the console and engine.
-	Channel(SendError<ToConsole>)
+	Channel(SendError<ToConsole>),
+	/// An error returned by the [`Sink`](crate::Sink) receiving the score's events.
+	Sink(SinkError)
 }
\ No newline at end of file
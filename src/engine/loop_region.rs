@@ -0,0 +1,20 @@
+/// A region of a score to be played repeatedly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LoopRegion {
+	/// The tick at which the region begins, and to which playback returns.
+	pub start: usize,
+	/// The tick at which the region ends. Playback returns to the start upon reaching this tick, so events at it are not played.
+	pub end: usize,
+	/// The number of times playback returns to the start before continuing past the end, or `None` to loop indefinitely.
+	pub repeats: Option<usize>,
+}
+
+impl LoopRegion {
+	pub fn new(start:usize, end:usize, repeats:Option<usize>) -> LoopRegion {
+		LoopRegion {
+			start,
+			end,
+			repeats
+		}
+	}
+}
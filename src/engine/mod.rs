@@ -1,129 +1,589 @@
-use std::time::Duration;
+use std::{borrow::Cow, sync::Arc, time::{Duration, Instant}};
 
-use midly::Timing;
+use midly::{num::u4, MidiMessage};
 
 use crate::{
+	clock::Clock,
 	messages::{ToConsole, ToEngine},
-	score::{Event, MetaEvent, Score},
-	sleep,
+	notification::Notification,
+	score::{Chase, Event, MetaEvent, MidiEvent, Score},
+	sink::Sink,
+	sleep::Wake,
+	timestamp::Timestamp,
 	timer::Timer
 };
 
 mod error;
 pub use error::Error;
+mod channel_map;
+pub use channel_map::ChannelMap;
+mod loop_region;
+pub use loop_region::LoopRegion;
+mod mute;
+pub use mute::Mute;
+mod note_release;
+pub use note_release::NoteRelease;
+mod pipeline;
+use pipeline::Pipeline;
+mod playlist;
+use playlist::Playlist;
+mod render;
+pub use render::Render;
+mod sounding_notes;
+use sounding_notes::SoundingNotes;
+mod transpose;
+pub use transpose::{OutOfRangeNotes, Transpose, TransposedNotes};
+mod velocity;
+pub use velocity::{Velocity, VelocitySettings, VelocityTarget};
+use velocity::ChannelVolumes;
 
+#[cfg(test)]
+mod tests;
+
+/// The longest the engine waits between performances while playing, so that the position reported to the console
+/// stays current during long stretches without events.
 const MAXIMUM_SLEEP_DURATION:Duration = Duration::from_millis(10);
 
 pub fn engine(
 	channel_from_console: &crossbeam_channel::Receiver<ToEngine>,
-	channel_to_console: &crossbeam_channel::Sender<ToConsole>,
-	score: &Score,
-	timing: Timing
+	channel_to_console: crossbeam_channel::Sender<ToConsole>,
+	sink: Box<dyn Sink>,
+	clock: Box<dyn Clock>,
+	score: Arc<Score>
 ) -> Result<(), Error> {
 	//timer
-		let mut timer = match Timer::new(timing, MAXIMUM_SLEEP_DURATION) {
+		let timer = match Timer::new(score.get_timing(), MAXIMUM_SLEEP_DURATION) {
 			Ok(timer) => timer,
 			Err(_err) => {
 				return Err(Error::TimerCreation)
 			}
 		};
 
-	//loop control
-		let mut halt = false;
+	let mut engine = Engine::new(channel_to_console, sink, clock, Playlist::new(score), timer);
+
+
+	loop {
+		//wait for a message, or the time to perform
+			match engine.clock.wait(channel_from_console, engine.deadline) {
+				Wake::Message(ToEngine::Halt) | Wake::Disconnected => {
+					engine.release()?;
+					return Ok(());
+				},
+				Wake::Message(message) => engine.handle(message)?,
+				Wake::Deadline => engine.perform()?,
+			}
+	}
+}
+
+struct Engine {
+	channel_to_console: crossbeam_channel::Sender<ToConsole>,
+	sink: Box<dyn Sink>,
+	clock: Box<dyn Clock>,
+	playlist: Playlist,
+	timer: Timer,
 
 	//performance control
-		let mut play = false;
-		let mut position:usize = 0;
-		let mut speed:f32 = 1.0;
-		let mut looping = false;
-
-	while !halt {
-		//check for messages
-			channel_from_console.try_iter().for_each(|message| {
-				match message {
-					ToEngine::Halt => halt = true,
-					ToEngine::Play => play = true,
-					ToEngine::Pause => play = false,
-					ToEngine::Stop => {
-						play = false;
-						position = 0;
-						if let Some(microseconds_per_beat) = score.get_microseconds_per_beat_at(position) {
-							timer.change_tempo(u32::from(microseconds_per_beat));
-						}
-					},
-					ToEngine::JumpTo(new_position) => {
-						position = new_position;
-						if let Some(microseconds_per_beat) = score.get_microseconds_per_beat_at(position) {
-							timer.change_tempo(u32::from(microseconds_per_beat));
+		play: bool,
+		/// When to next perform, or `None` if not playing.
+		deadline: Option<Instant>,
+		/// How long was left until the next performance when playback was paused.
+		paused_remainder: Duration,
+		/// How far ahead of time events are sent.
+		lookahead: Duration,
+		/// The moment the events being performed are meant to be heard, while performing.
+		performance_instant: Option<Instant>,
+		/// The timestamp of the events being performed, while performing them.
+		performance_timestamp: Option<Timestamp>,
+		position: usize,
+		looping: bool,
+
+	//loop region
+		loop_region: Option<LoopRegion>,
+		loop_count: usize,
+		/// The position last performed, before stepping on to the current one (`None` if playback has since been moved).
+		stepped_from: Option<usize>,
+
+	//note tracking
+		sounding_notes: SoundingNotes,
+		note_release: NoteRelease,
+
+	//filtering
+		track_mute: Mute<usize>,
+		channel_mute: Mute<u4>,
+		channel_map: ChannelMap,
+		transpose: Transpose,
+		transposed_notes: TransposedNotes,
+		velocity: VelocitySettings,
+		channel_volumes: ChannelVolumes,
+		pipeline: Pipeline,
+
+	//state chasing
+		chase: Chase,
+}
+
+impl Engine {
+	fn new(
+		channel_to_console: crossbeam_channel::Sender<ToConsole>,
+		sink: Box<dyn Sink>,
+		clock: Box<dyn Clock>,
+		playlist: Playlist,
+		timer: Timer
+	) -> Engine {
+		Engine {
+			channel_to_console,
+			sink,
+			clock,
+			playlist,
+			timer,
+
+			play: false,
+			deadline: None,
+			paused_remainder: Duration::ZERO,
+			lookahead: Duration::ZERO,
+			performance_instant: None,
+			performance_timestamp: None,
+			position: 0,
+			looping: false,
+
+			loop_region: None,
+			loop_count: 0,
+			stepped_from: None,
+
+			sounding_notes: SoundingNotes::default(),
+			note_release: NoteRelease::default(),
+
+			track_mute: Mute::default(),
+			channel_mute: Mute::default(),
+			channel_map: ChannelMap::default(),
+			transpose: Transpose::default(),
+			transposed_notes: TransposedNotes::default(),
+			velocity: VelocitySettings::default(),
+			channel_volumes: ChannelVolumes::default(),
+			pipeline: Pipeline::default(),
+
+			chase: Chase::default(),
+		}
+	}
+}
+
+impl Engine {
+	fn handle(&mut self, message:ToEngine) -> Result<(), Error> {
+		match message {
+			// (halting is handled by the engine loop)
+			ToEngine::Halt => {},
+			ToEngine::Play => {
+				if !self.play {
+					self.play = true;
+					let delay = std::mem::take(&mut self.paused_remainder);
+					self.resume(delay);
+				}
+			},
+			ToEngine::Pause => {
+				if let (Some(_), Some(last_instant)) = (self.deadline, self.timer.get_last_instant()) {
+					self.paused_remainder = last_instant.saturating_duration_since(self.clock.now());
+				}
+				self.play = false;
+				self.deadline = None;
+				self.release()?;
+			},
+			ToEngine::Stop => {
+				self.play = false;
+				self.deadline = None;
+				self.loop_count = 0;
+				self.relocate(0)?;
+			},
+			ToEngine::JumpTo(new_position) => {
+				self.relocate(new_position)?;
+				self.resume(Duration::ZERO);
+			},
+			ToEngine::SetSpeed(new_speed) => {
+				if new_speed > 0.0 {
+					// (what was left of the event paused on is left in score time, so is taken at the new speed too)
+					self.paused_remainder = self.paused_remainder.mul_f32(self.timer.get_speed() / new_speed);
+					self.timer.change_speed(new_speed, self.clock.now());
+					self.reschedule();
+				}
+			},
+			ToEngine::SetLooping(new_state) => {
+				self.looping = new_state;
+			},
+			ToEngine::SetLoopRegion(new_loop_region) => {
+				self.loop_region = new_loop_region;
+				self.loop_count = 0;
+			},
+			ToEngine::SetNoteRelease(new_note_release) => {
+				self.note_release = new_note_release;
+			},
+			ToEngine::SetChase(new_chase) => {
+				self.chase = new_chase;
+			},
+			ToEngine::MuteTrack(track_index, muted) => {
+				self.track_mute.set_muted(track_index, muted);
+				self.release_inaudible()?;
+			},
+			ToEngine::SoloTrack(track_index, soloed) => {
+				self.track_mute.set_soloed(track_index, soloed);
+				self.release_inaudible()?;
+			},
+			ToEngine::SetTranspose(new_transpose) => {
+				self.transpose = new_transpose;
+			},
+			ToEngine::SetVelocity(new_velocity) => {
+				let old_velocity = std::mem::replace(&mut self.velocity, new_velocity);
+				for (track_index, midi_event) in self.channel_volumes.refresh(&old_velocity, &self.velocity) {
+					let source = midi_event.channel;
+					if let Some(midi_event) = self.route(track_index, midi_event) {
+						self.send(track_index, Some(source), Cow::Owned(Event::Midi(midi_event)))?;
+					}
+				}
+			},
+			ToEngine::InsertProcessor(index, id, processor) => {
+				self.release()?;
+				self.pipeline.insert(index, id, processor);
+			},
+			ToEngine::RemoveProcessor(id) => {
+				self.release()?;
+				if !self.pipeline.remove(id) {
+					self.notify(Notification::NoSuchProcessor(id))?;
+				}
+			},
+			ToEngine::MoveProcessor(id, index) => {
+				self.release()?;
+				if !self.pipeline.move_to(id, index) {
+					self.notify(Notification::NoSuchProcessor(id))?;
+				}
+			},
+			ToEngine::MuteChannel(channel, muted) => {
+				self.channel_mute.set_muted(channel, muted);
+				self.release_inaudible()?;
+			},
+			ToEngine::SoloChannel(channel, soloed) => {
+				self.channel_mute.set_soloed(channel, soloed);
+				self.release_inaudible()?;
+			},
+			ToEngine::RemapChannel(channel, destination) => {
+				// (notes sent to the old destination would never receive their note offs)
+				let old_destination = self.channel_map.get(channel);
+				if old_destination != destination {
+					self.channel_map.set(channel, destination);
+					for (track_index, midi_event) in self.sounding_notes.release_matching(self.note_release, |_track_index, source| source == channel) {
+						self.emit(track_index, midi_event)?;
+					}
+				}
+			},
+			ToEngine::SetLookahead(new_lookahead) => {
+				self.lookahead = new_lookahead;
+				self.reschedule();
+			},
+			ToEngine::Load(score, load_position) => {
+				// (the item being played is replaced, which may have moved on since the console asked)
+				let item = self.playlist.get_current_item();
+				let new_position = load_position.resolve(&self.score(), &score, self.position);
+				self.playlist.replace(item, Arc::clone(&score));
+				if self.timer.change_timing(score.get_timing()).is_err() {
+					return Err(Error::TimerCreation);
+				}
+
+				self.loop_region = None;
+				self.loop_count = 0;
+				self.relocate(new_position)?;
+				self.resume(Duration::ZERO);
+
+				if let Err(err) = self.channel_to_console.send(ToConsole::ItemReloaded(item, score, new_position)) {
+					return Err(Error::Channel(err))
+				}
+			},
+			ToEngine::Enqueue(score) => {
+				self.playlist.push(score);
+			},
+			ToEngine::SkipToItem(item) => {
+				if self.playlist.select(item) {
+					self.start_item()?;
+					self.resume(Duration::ZERO);
+				}
+			},
+			ToEngine::SkipToNextItem => {
+				if self.playlist.advance().is_some() {
+					self.start_item()?;
+					self.resume(Duration::ZERO);
+				} else {
+					self.release()?;
+					self.finish()?;
+				}
+			},
+			ToEngine::SetShuffle(new_state) => {
+				self.playlist.set_shuffle(new_state);
+			},
+			ToEngine::SetRepeatAll(new_state) => {
+				self.playlist.set_repeat_all(new_state);
+			},
+		}
+
+		Ok(())
+	}
+
+	/// Move the playhead, setting the tempo for the new position, releasing any sounding notes and chasing the channel state.
+	fn relocate(&mut self, position:usize) -> Result<(), Error> {
+		let score = self.score();
+
+		self.position = position;
+		self.stepped_from = None;
+		self.paused_remainder = Duration::ZERO;
+		if let Some(microseconds_per_beat) = score.get_microseconds_per_beat_at(position) {
+			self.timer.change_tempo(u32::from(microseconds_per_beat));
+		}
+		self.release()?;
+
+		for (track_index, midi_event) in score.gather_chase_events_until(position, self.chase) {
+			let source = midi_event.channel;
+			if let Some(midi_event) = self.route(track_index, midi_event) {
+				self.send(track_index, Some(source), Cow::Owned(Event::Midi(midi_event)))?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// If playing, perform the current position after the given delay, timing from then rather than from the last
+	/// performance.
+	fn resume(&mut self, delay:Duration) {
+		if self.play {
+			let instant = self.clock.now() + delay;
+			self.timer.restart_at(instant);
+			self.deadline = Some(instant);
+		}
+	}
+
+	/// If playing, bring the deadline in line with the instant the timer has scheduled for the next events.
+	fn reschedule(&mut self) {
+		if self.deadline.is_some() {
+			self.deadline = self.timer.get_last_instant().map(|instant| self.deadline_for(instant));
+		}
+	}
+
+	/// The moment to perform events meant to be heard at the given instant.
+	fn deadline_for(&self, instant:Instant) -> Instant {
+		instant.checked_sub(self.lookahead).unwrap_or(instant)
+	}
+
+	/// The [Timestamp] for events sent now, at the current position.
+	fn timestamp(&self) -> Timestamp {
+		if let Some(timestamp) = self.performance_timestamp {
+			return timestamp;
+		}
+
+		Timestamp {
+			tick: self.position,
+			instant: self.performance_instant.unwrap_or_else(|| self.clock.now()),
+			time: self.score().calculate_duration_until(self.timer.get_speed(), self.position),
+		}
+	}
+
+	/// Begin the playlist's current item from its start, switching the timer over to its timing.
+	fn start_item(&mut self) -> Result<(), Error> {
+		let item = self.playlist.get_current_item();
+		if self.timer.change_timing(self.score().get_timing()).is_err() {
+			return Err(Error::TimerCreation);
+		}
+
+		self.loop_region = None;
+		self.loop_count = 0;
+		self.relocate(0)?;
+
+		if let Err(err) = self.channel_to_console.send(ToConsole::ItemUpdate(item)) {
+			return Err(Error::Channel(err))
+		}
+		self.notify(Notification::ItemStarted(item))
+	}
+
+	/// Stop playing, having run out of things to play.
+	fn finish(&mut self) -> Result<(), Error> {
+		self.play = false;
+		self.deadline = None;
+		if let Err(err) = self.channel_to_console.send(ToConsole::Stopped) {
+			return Err(Error::Channel(err))
+		}
+		Ok(())
+	}
+
+	fn score(&self) -> Arc<Score> {
+		Arc::clone(self.playlist.get_current_score())
+	}
+
+	/// Run an event (already routed) through the pipeline and send it on, keeping track of the notes it sounds by the
+	/// channel it came from in the score (`source`, if it had one).
+	fn send(&mut self, track_index:usize, source:Option<u4>, event:Cow<'_, Event>) -> Result<(), Error> {
+		let Some(event) = self.pipeline.run(track_index, event) else {
+			return Ok(());
+		};
+
+		if let Event::Midi(midi_event) = event.as_ref() {
+			self.sounding_notes.register(track_index, source.unwrap_or(midi_event.channel), midi_event);
+		}
+
+		let timestamp = self.timestamp();
+		self.sink.receive_timestamped(track_index, &event, &timestamp).map_err(Error::Sink)
+	}
+
+	/// Send a [`MidiEvent`] as it is, bypassing the pipeline (for the messages that release notes).
+	fn emit(&mut self, track_index:usize, midi_event:MidiEvent) -> Result<(), Error> {
+		let timestamp = self.timestamp();
+		self.sink.receive_timestamped(track_index, &Event::Midi(midi_event), &timestamp).map_err(Error::Sink)
+	}
+
+	fn notify(&mut self, notification:Notification) -> Result<(), Error> {
+		self.sink.notify(&notification).map_err(Error::Sink)
+	}
+
+	fn release(&mut self) -> Result<(), Error> {
+		self.transposed_notes.clear();
+		self.pipeline.reset();
+		for (track_index, midi_event) in self.sounding_notes.release(self.note_release) {
+			self.emit(track_index, midi_event)?;
+		}
+
+		Ok(())
+	}
+
+	/// Release the notes sounding on any tracks or channels which can no longer be heard.
+	fn release_inaudible(&mut self) -> Result<(), Error> {
+		// (notes are recorded by the channel they came from, so other channels remapped to the same destination play on)
+		let track_mute = &self.track_mute;
+		let channel_mute = &self.channel_mute;
+		for (track_index, midi_event) in self.sounding_notes.release_matching(
+			self.note_release,
+			|track_index, source| !track_mute.is_audible(track_index) || !channel_mute.is_audible(source)
+		) {
+			self.emit(track_index, midi_event)?;
+		}
+
+		Ok(())
+	}
+
+	/// Prepare an event from the given track to be sent on (see [`Engine::route`]).
+	fn route_event<'a>(&mut self, track_index:usize, event:&'a Event) -> Option<Cow<'a, Event>> {
+		match event {
+			Event::Midi(midi_event) => self.route(track_index, *midi_event).map(|midi_event| Cow::Owned(Event::Midi(midi_event))),
+			_ => Some(Cow::Borrowed(event)),
+		}
+	}
+
+	/// Prepare a [`MidiEvent`] from the given track to be sent on, transposing it, changing its velocity (or volume)
+	/// and moving it to the channel it has been remapped to, or return `None` if it is to be held back. Only the notes of muted tracks and channels (and
+	/// notes transposed out of range) are held back, so that they still keep their channels up to date.
+	fn route(&mut self, track_index:usize, midi_event:MidiEvent) -> Option<MidiEvent> {
+		let is_note_on = matches!(midi_event.message, MidiMessage::NoteOn { vel, .. } if u8::from(vel) > 0);
+		if is_note_on && !(self.track_mute.is_audible(track_index) && self.channel_mute.is_audible(midi_event.channel)) {
+			return None;
+		}
+
+		self.channel_volumes.register(track_index, &midi_event);
+		let midi_event = self.transposed_notes.apply(&self.transpose, track_index, midi_event)?;
+		let midi_event = self.velocity.apply_to(track_index, midi_event);
+
+		Some(
+			MidiEvent {
+				channel: self.channel_map.get(midi_event.channel),
+				message: midi_event.message
+			}
+		)
+	}
+}
+
+impl Engine {
+	/// Perform the events at the current position, setting the deadline for the next call.
+	fn perform(&mut self) -> Result<(), Error> {
+		if !self.play {
+			self.deadline = None;
+			return Ok(());
+		}
+
+		self.performance_instant = Some(self.timer.get_last_instant().unwrap_or_else(|| self.clock.now()));
+		let result = self.perform_events();
+		self.performance_instant = None;
+		self.performance_timestamp = None;
+		result
+	}
+
+	fn perform_events(&mut self) -> Result<(), Error> {
+		let score = self.score();
+
+		//loop region
+			// (the region may have been set after stepping past its end, so its end is looked for within the step)
+			if let Some(loop_region) = self.loop_region {
+				let reached_end = match self.stepped_from {
+					Some(stepped_from) => stepped_from < loop_region.end && loop_region.end <= self.position,
+					None => self.position == loop_region.end,
+				};
+				if reached_end && loop_region.repeats.is_none_or(|repeats| self.loop_count < repeats) {
+					self.loop_count += 1;
+					self.relocate(loop_region.start)?;
+					self.notify(Notification::LoopWrapped(self.loop_count))?;
+				}
+			}
+
+		//transmit position
+			if let Err(err) = self.channel_to_console.send(ToConsole::PositionUpdate(self.position)) {
+				return Err(Error::Channel(err))
+			}
+
+		if let Some(simultaneous_events_per_track) = score.gather_all_events_for_index(self.position) {
+			//process events for this position
+				self.performance_timestamp = Some(self.timestamp());
+				for (track_index, simultaneous_events) in simultaneous_events_per_track {
+					for event in &simultaneous_events.events {
+						// (tempo changes are followed whatever becomes of the event)
+						if let Event::Meta(MetaEvent::Tempo(microseconds_per_beat)) = event {
+							self.timer.change_tempo(u32::from(*microseconds_per_beat));
 						}
-					},
-					ToEngine::SetSpeed(new_speed) => {
-						if new_speed > 0.0 {
-							speed = new_speed;
-							timer.set_speed(speed);
+
+						let source = if let Event::Midi(midi_event) = event { Some(midi_event.channel) } else { None };
+						if let Some(event) = self.route_event(track_index, event) {
+							self.send(track_index, source, event)?;
 						}
-					},
-					ToEngine::SetLooping(new_state) => {
-						looping = new_state;
-					},
+					}
 				}
-			});
 
-		//perform
-			let sleep_duration = if play {
-				//transmit position
-					if let Err(err) = channel_to_console.send(ToConsole::PositionUpdate(position)) {
-						return Err(Error::Channel(err))
-					}
+			//notify of lyrics and markers for this position
+				for syllable_index in score.get_lyrics().get_syllable_indices_at(self.position) {
+					self.notify(Notification::Syllable(syllable_index))?;
+				}
+				for marker_index in score.get_marker_indices_at(self.position) {
+					self.notify(Notification::Marker(marker_index))?;
+				}
 
-				if let Some(simultaneous_events_per_track) = score.gather_all_events_for_index(position) {
-					//process events for this position
-						for (track_index, simultaneous_events) in simultaneous_events_per_track {
-							for event in &simultaneous_events.events {
-								if let Event::Meta(MetaEvent::Tempo(microseconds_per_beat)) = event {
-									timer.change_tempo(u32::from(*microseconds_per_beat));
-								}
-
-								if let Err(err) = channel_to_console.send(ToConsole::Event(track_index, event.clone())) {
-									return Err(Error::Channel(err))
-								}
+			//calculate sleep until next event
+				//skipping method with maximum sleep duration
+					let ticks_until_next_event = score.calculate_ticks_until_next_events_from_index(self.position).unwrap_or(1);
+					let mut ticks = if ticks_until_next_event > self.timer.get_number_of_ticks_that_would_fit_into_maximum_sleep_time() {
+						self.timer.get_number_of_ticks_that_would_fit_into_maximum_sleep_time()
+					} else {
+						ticks_until_next_event
+					};
+					//don't skip over the end of the loop region
+						if let Some(loop_region) = self.loop_region {
+							if self.position < loop_region.end && self.position + ticks > loop_region.end {
+								ticks = loop_region.end - self.position;
 							}
 						}
+					let instant = self.timer.schedule(ticks, self.clock.now());
+					self.deadline = Some(self.deadline_for(instant));
+					self.stepped_from = Some(self.position);
+					self.position += ticks;
 
-					//calculate sleep until next event
-						//skipping method with maximum sleep duration
-							let ticks_until_next_event = score.calculate_ticks_until_next_events_from_index(position).unwrap_or(1);
-							let ticks = if ticks_until_next_event > timer.get_number_of_ticks_that_would_fit_into_maximum_sleep_time() {
-								timer.get_number_of_ticks_that_would_fit_into_maximum_sleep_time()
-							} else {
-								ticks_until_next_event
-							};
-							let sleep_duration = timer.calculate_sleeping_time(ticks);
-							position += ticks;
-
-						// //dumb method
-						// 	let sleep_duration = timer.calculate_duration_of_ticks(1);
-						// 	position += 1;
-
-					//return desired sleep duration
-						sleep_duration
-				} else if looping {
-					position = 0;
-					Duration::ZERO
-				} else {
-					play = false;
-					if let Err(err) = channel_to_console.send(ToConsole::Stopped) {
-						return Err(Error::Channel(err))
-					}
-					Duration::ZERO
-				}
-			} else {
-				MAXIMUM_SLEEP_DURATION
-			};
+				// //dumb method
+				// 	self.deadline = Some(self.timer.schedule(1, self.clock.now()));
+				// 	self.position += 1;
+		} else if self.looping {
+			self.relocate(0)?;
+			self.deadline = Some(self.clock.now());
+		} else if self.playlist.advance().is_some() {
+			self.start_item()?;
+			self.deadline = Some(self.clock.now());
+		} else {
+			self.finish()?;
+		}
 
-		//sleep
-			sleep::sleep(sleep_duration);
+		Ok(())
 	}
-
-	Ok(())
-}
\ No newline at end of file
+}
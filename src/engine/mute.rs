@@ -0,0 +1,52 @@
+use std::collections::BTreeSet;
+
+/// Which parts of a score (tracks or channels) have been muted or soloed. While anything is soloed, only the soloed
+/// parts that aren't also muted can be heard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mute<T> {
+	muted: BTreeSet<T>,
+	soloed: BTreeSet<T>,
+}
+
+impl<T> Default for Mute<T> {
+	fn default() -> Mute<T> {
+		Mute {
+			muted: BTreeSet::new(),
+			soloed: BTreeSet::new()
+		}
+	}
+}
+
+impl<T:Ord + Copy> Mute<T> {
+	pub fn set_muted(&mut self, part:T, muted:bool) {
+		if muted {
+			self.muted.insert(part);
+		} else {
+			self.muted.remove(&part);
+		}
+	}
+	pub fn set_soloed(&mut self, part:T, soloed:bool) {
+		if soloed {
+			self.soloed.insert(part);
+		} else {
+			self.soloed.remove(&part);
+		}
+	}
+
+	pub fn is_muted(&self, part:T) -> bool {
+		self.muted.contains(&part)
+	}
+	pub fn is_soloed(&self, part:T) -> bool {
+		self.soloed.contains(&part)
+	}
+	pub fn is_audible(&self, part:T) -> bool {
+		!self.is_muted(part) && (self.soloed.is_empty() || self.is_soloed(part))
+	}
+
+	pub fn get_muted(&self) -> impl Iterator<Item = T> + '_ {
+		self.muted.iter().copied()
+	}
+	pub fn get_soloed(&self) -> impl Iterator<Item = T> + '_ {
+		self.soloed.iter().copied()
+	}
+}
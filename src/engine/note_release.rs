@@ -0,0 +1,41 @@
+/// The messages the engine sends to silence any sounding notes when playback is interrupted
+/// (by pausing, stopping, jumping, looping or dropping the [`Performer`](crate::Performer)).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NoteRelease {
+	/// Send a "Note Off" message for every note that is currently sounding.
+	pub note_offs: bool,
+	/// Send an "All Notes Off" message (controller 123) to every channel that has played a note.
+	pub all_notes_off: bool,
+	/// Send a sustain pedal release (controller 64, value 0) to every channel holding the pedal down.
+	pub sustain_release: bool,
+}
+
+impl Default for NoteRelease {
+	fn default() -> NoteRelease {
+		NoteRelease {
+			note_offs: true,
+			all_notes_off: false,
+			sustain_release: false
+		}
+	}
+}
+
+impl NoteRelease {
+	/// A [`NoteRelease`] which sends nothing, leaving any sounding notes to ring.
+	pub fn none() -> NoteRelease {
+		NoteRelease {
+			note_offs: false,
+			all_notes_off: false,
+			sustain_release: false
+		}
+	}
+
+	/// A [`NoteRelease`] which sends every kind of release message.
+	pub fn all() -> NoteRelease {
+		NoteRelease {
+			note_offs: true,
+			all_notes_off: true,
+			sustain_release: true
+		}
+	}
+}
@@ -0,0 +1,57 @@
+use std::borrow::Cow;
+
+use crate::{processor::{Processor, ProcessorId}, score::Event};
+
+/// The [Processor]s each event passes through before being sent, in order.
+#[derive(Debug, Default)]
+pub struct Pipeline {
+	processors: Vec<(ProcessorId, Box<dyn Processor>)>,
+}
+
+impl Pipeline {
+	/// Insert a processor at the given index (or at the end, if the index is beyond it).
+	pub fn insert(&mut self, index:usize, id:ProcessorId, processor:Box<dyn Processor>) {
+		self.processors.insert(index.min(self.processors.len()), (id, processor));
+	}
+
+	/// Remove a processor, returning whether it was found.
+	pub fn remove(&mut self, id:ProcessorId) -> bool {
+		let length = self.processors.len();
+		self.processors.retain(|(processor_id, _)| *processor_id != id);
+		self.processors.len() != length
+	}
+
+	/// Move a processor to the given index (or to the end, if the index is beyond it), returning whether it was found.
+	pub fn move_to(&mut self, id:ProcessorId, index:usize) -> bool {
+		let Some(current_index) = self.processors.iter().position(|(processor_id, _)| *processor_id == id) else {
+			return false;
+		};
+
+		let processor = self.processors.remove(current_index);
+		self.processors.insert(index.min(self.processors.len()), processor);
+		true
+	}
+
+	/// Run an event from the given track through every processor, returning `None` if any of them dropped it.
+	pub fn run<'a>(&mut self, track_index:usize, event:Cow<'a, Event>) -> Option<Cow<'a, Event>> {
+		// (the event is only copied if there's something to change it)
+		if self.processors.is_empty() {
+			return Some(event);
+		}
+
+		self.processors
+			.iter_mut()
+			.try_fold(event.into_owned(), |event, (_, processor)| processor.process(track_index, event))
+			.map(Cow::Owned)
+	}
+
+	/// Reset every processor, as every sounding note has been released.
+	pub fn reset(&mut self) {
+		self.processors.iter_mut().for_each(|(_, processor)| processor.reset());
+	}
+
+	#[cfg(test)]
+	pub fn get_ids(&self) -> Vec<ProcessorId> {
+		self.processors.iter().map(|(id, _)| *id).collect()
+	}
+}
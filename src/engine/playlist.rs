@@ -0,0 +1,129 @@
+use std::{sync::Arc, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::score::Score;
+
+/// The queue of scores played by the engine, one after another.
+#[derive(Debug, Clone)]
+pub struct Playlist {
+	items: Vec<Arc<Score>>,
+	// the indices of the items in the order they are to be played
+	order: Vec<usize>,
+	// the index (into `order`) of the current item
+	order_index: usize,
+
+	shuffle: bool,
+	repeat_all: bool,
+	random_state: u64,
+}
+
+impl Playlist {
+	pub fn new(score:Arc<Score>) -> Playlist {
+		Playlist {
+			items: vec![score],
+			order: vec![0],
+			order_index: 0,
+
+			shuffle: false,
+			repeat_all: false,
+			random_state: SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map_or(0, |duration| duration.as_nanos() as u64)
+				| 1,
+		}
+	}
+}
+
+impl Playlist {
+	pub fn get_current_item(&self) -> usize {
+		self.order[self.order_index]
+	}
+	pub fn get_current_score(&self) -> &Arc<Score> {
+		&self.items[self.get_current_item()]
+	}
+}
+
+impl Playlist {
+	/// Add a score to the end of the playlist (or, when shuffling, somewhere among the items yet to be played).
+	pub fn push(&mut self, score:Arc<Score>) {
+		self.items.push(score);
+		let item = self.items.len() - 1;
+
+		if self.shuffle {
+			let remaining = self.order.len() - self.order_index;
+			let position = self.order_index + 1 + self.random_below(remaining);
+			self.order.insert(position, item);
+		} else {
+			self.order.push(item);
+		}
+	}
+
+	/// Replace the score of the item with the given index, returning `false` if there is no such item.
+	pub fn replace(&mut self, item:usize, score:Arc<Score>) -> bool {
+		let Some(item_score) = self.items.get_mut(item) else {
+			return false;
+		};
+
+		*item_score = score;
+		true
+	}
+
+	/// Make the item with the given index the current item, returning `false` if there is no such item.
+	pub fn select(&mut self, item:usize) -> bool {
+		let Some(order_index) = self.order.iter().position(|ordered_item| *ordered_item == item) else {
+			return false;
+		};
+
+		self.order_index = order_index;
+		true
+	}
+
+	/// Move on to the next item, returning its index, or `None` if the end of the playlist has been reached
+	/// (and it isn't set to repeat).
+	pub fn advance(&mut self) -> Option<usize> {
+		if self.order_index + 1 < self.order.len() {
+			self.order_index += 1;
+		} else if self.repeat_all {
+			self.order_index = 0;
+			if self.shuffle {
+				self.shuffle_from(0);
+			}
+		} else {
+			return None;
+		}
+
+		Some(self.get_current_item())
+	}
+}
+
+impl Playlist {
+	pub fn set_shuffle(&mut self, shuffle:bool) {
+		self.shuffle = shuffle;
+
+		let current_item = self.get_current_item();
+		if shuffle {
+			self.shuffle_from(self.order_index + 1);
+		} else {
+			self.order = (0..self.items.len()).collect();
+			self.order_index = current_item;
+		}
+	}
+	pub fn set_repeat_all(&mut self, repeat_all:bool) {
+		self.repeat_all = repeat_all;
+	}
+
+	/// Shuffle the order of the items from the given index (into `order`) onwards.
+	fn shuffle_from(&mut self, start:usize) {
+		for index in (start + 1..self.order.len()).rev() {
+			let other_index = start + self.random_below(index - start + 1);
+			self.order.swap(index, other_index);
+		}
+	}
+
+	/// A pseudo-random number in the range `0..limit` (xorshift).
+	fn random_below(&mut self, limit:usize) -> usize {
+		self.random_state ^= self.random_state << 13;
+		self.random_state ^= self.random_state >> 7;
+		self.random_state ^= self.random_state << 17;
+		(self.random_state % limit as u64) as usize
+	}
+}
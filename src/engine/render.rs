@@ -0,0 +1,129 @@
+use std::{collections::VecDeque, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use crate::{
+	clock::{Clock, VirtualClock},
+	messages::{ToConsole, ToEngine},
+	score::{Event, MidiEvent, Score},
+	sink::{Sink, SinkError},
+	timer::Timer,
+	timestamp::Timestamp
+};
+
+use super::{Engine, Error, Playlist, MAXIMUM_SLEEP_DURATION};
+
+type RenderedEvents = Arc<Mutex<VecDeque<(Duration, usize, Event)>>>;
+
+/// An iterator over the events of a performance, produced as fast as possible rather than in real time.
+///
+/// Each event is given along with its track number and the time (from the start of the render) at which real-time
+/// playback would have sent it. The iterator ends when playback would have stopped, so a looping performance
+/// produces events indefinitely.
+///
+/// The iterator also ends early if the engine encounters an error, which is then given by [`Render::error`].
+pub struct Render {
+	engine: Engine,
+	clock: Arc<VirtualClock>,
+	events: RenderedEvents,
+	channel_from_engine: crossbeam_channel::Receiver<ToConsole>,
+	error: Option<Error>,
+}
+
+impl Render {
+	/// Set up an engine running against a virtual clock, playing the given scores, and prepare it with the
+	/// given messages.
+	pub(crate) fn new(scores:&[Arc<Score>], messages:Vec<ToEngine>) -> Result<Render, Error> {
+		let Some((first_score, other_scores)) = scores.split_first() else {
+			return Err(Error::EmptyPlaylist);
+		};
+		let mut playlist = Playlist::new(Arc::clone(first_score));
+		other_scores.iter().for_each(|score| playlist.push(Arc::clone(score)));
+
+		let Ok(timer) = Timer::new(playlist.get_current_score().get_timing(), MAXIMUM_SLEEP_DURATION) else {
+			return Err(Error::TimerCreation);
+		};
+
+		let clock = Arc::new(VirtualClock::new(Instant::now()));
+		let events = RenderedEvents::default();
+		let sink = RenderSink {
+			start: clock.now(),
+			events: Arc::clone(&events),
+		};
+		let (channel_to_console, channel_from_engine) = crossbeam_channel::unbounded::<ToConsole>();
+
+		let mut engine = Engine::new(channel_to_console, Box::new(sink), Box::new(Arc::clone(&clock)), playlist, timer);
+		for message in messages {
+			engine.handle(message)?;
+		}
+
+		Ok(
+			Render {
+				engine,
+				clock,
+				events,
+				channel_from_engine,
+				error: None,
+			}
+		)
+	}
+
+	/// Get the error which ended the render early, if any.
+	pub fn error(&self) -> Option<&Error> {
+		self.error.as_ref()
+	}
+
+	/// Take the error which ended the render early, if any.
+	pub fn take_error(&mut self) -> Option<Error> {
+		self.error.take()
+	}
+}
+
+impl Iterator for Render {
+	type Item = (Duration, usize, Event);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if self.error.is_some() {
+				return None;
+			}
+
+			let Ok(mut events) = self.events.lock() else {
+				self.error = Some(Error::Sink("the rendered events are unavailable".into()));
+				return None;
+			};
+			if let Some(event) = events.pop_front() {
+				return Some(event);
+			}
+			drop(events);
+
+			let deadline = self.engine.deadline?;
+			self.clock.advance_to(deadline);
+			if let Err(err) = self.engine.perform() {
+				self.error = Some(err);
+				return None;
+			}
+
+			// (the console messages are of no interest, but shouldn't be left to pile up)
+			self.channel_from_engine.try_iter().for_each(drop);
+		}
+	}
+}
+
+/// A sink which collects events, along with the (virtual) time at which they are meant to be heard.
+struct RenderSink {
+	start: Instant,
+	events: RenderedEvents,
+}
+
+impl Sink for RenderSink {
+	fn receive(&mut self, _track:usize, _midi_event:MidiEvent) -> Result<(), SinkError> {
+		Ok(())
+	}
+
+	fn receive_timestamped(&mut self, track:usize, event:&Event, timestamp:&Timestamp) -> Result<(), SinkError> {
+		self.events
+			.lock()
+			.map_err(|_| "the rendered events are unavailable")?
+			.push_back((timestamp.instant - self.start, track, event.clone()));
+		Ok(())
+	}
+}
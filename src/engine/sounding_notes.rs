@@ -0,0 +1,113 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use midly::{num::{u4, u7}, MidiMessage};
+
+use crate::score::MidiEvent;
+
+use super::NoteRelease;
+
+const SUSTAIN_PEDAL_CONTROLLER:u8 = 64;
+const ALL_NOTES_OFF_CONTROLLER:u8 = 123;
+
+/// A record of the notes (and sustain pedals) currently held down by each track and channel, kept by the channel
+/// they came from as well as the channel they were sent to.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SoundingNotes {
+	// (track index, source channel, channel, key) > number of times the note has been started
+	notes: BTreeMap<(usize, u4, u4, u7), usize>,
+	sustained_channels: BTreeSet<(usize, u4, u4)>,
+	used_channels: BTreeSet<(usize, u4, u4)>
+}
+
+impl SoundingNotes {
+	/// Update the record with a [`MidiEvent`] that has been sent from the given track, having come from the `source`
+	/// channel.
+	pub fn register(&mut self, track_index:usize, source:u4, midi_event:&MidiEvent) {
+		let part = (track_index, source, midi_event.channel);
+		match midi_event.message {
+			MidiMessage::NoteOn { key, vel } if u8::from(vel) > 0 => {
+				*self.notes.entry((track_index, source, midi_event.channel, key)).or_insert(0) += 1;
+				self.used_channels.insert(part);
+			},
+			MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+				if let Some(count) = self.notes.get_mut(&(track_index, source, midi_event.channel, key)) {
+					*count -= 1;
+					if *count == 0 {
+						self.notes.remove(&(track_index, source, midi_event.channel, key));
+					}
+				}
+			},
+			MidiMessage::Controller { controller, value } if u8::from(controller) == SUSTAIN_PEDAL_CONTROLLER => {
+				if u8::from(value) >= 64 {
+					self.sustained_channels.insert(part);
+				} else {
+					self.sustained_channels.remove(&part);
+				}
+			},
+			_ => {}
+		}
+	}
+
+	/// Produce the messages required to silence everything in the record (as selected by `note_release`) and clear it.
+	pub fn release(&mut self, note_release:NoteRelease) -> Vec<(usize, MidiEvent)> {
+		self.release_matching(note_release, |_, _| true)
+	}
+
+	/// Produce the messages required to silence everything in the record sent from the tracks and source channels for
+	/// which `matching` returns true (as selected by `note_release`), and remove them from it.
+	///
+	/// Messages which affect a whole channel are not sent to channels still in use by anything left in the record.
+	pub fn release_matching(&mut self, note_release:NoteRelease, matching:impl Fn(usize, u4) -> bool) -> Vec<(usize, MidiEvent)> {
+		let is_matching = |(track_index, source, _):&(usize, u4, u4)| matching(*track_index, *source);
+		let kept_channels:BTreeSet<u4> = self.used_channels
+			.iter()
+			.chain(&self.sustained_channels)
+			.filter(|part| !is_matching(part))
+			.map(|(_, _, channel)| *channel)
+			.collect();
+
+		let mut output = vec![];
+
+		if note_release.sustain_release {
+			let (released, kept) = std::mem::take(&mut self.sustained_channels).into_iter().partition(is_matching);
+			self.sustained_channels = kept;
+			output.extend(
+				released
+					.into_iter()
+					.filter(|(_, _, channel)| !kept_channels.contains(channel))
+					.map(|(track_index, _, channel)| (track_index, controller_event(channel, SUSTAIN_PEDAL_CONTROLLER, 0)))
+			);
+		}
+
+		if note_release.note_offs {
+			output.extend(
+				self.notes
+					.keys()
+					.filter(|(track_index, source, _, _)| matching(*track_index, *source))
+					.map(|(track_index, _, channel, key)| (*track_index, MidiEvent { channel: *channel, message: MidiMessage::NoteOff { key: *key, vel: u7::new(0) } }))
+			);
+		}
+		self.notes.retain(|(track_index, source, _, _), _| !matching(*track_index, *source));
+
+		if note_release.all_notes_off {
+			let mut released_channels = BTreeSet::new();
+			output.extend(
+				self.used_channels
+					.iter()
+					.filter(|part| is_matching(part) && !kept_channels.contains(&part.2))
+					.filter(|(_, _, channel)| released_channels.insert(*channel))
+					.map(|(track_index, _, channel)| (*track_index, controller_event(*channel, ALL_NOTES_OFF_CONTROLLER, 0)))
+			);
+		}
+		self.used_channels.retain(|part| !is_matching(part));
+
+		output
+	}
+}
+
+fn controller_event(channel:u4, controller:u8, value:u8) -> MidiEvent {
+	MidiEvent {
+		channel,
+		message: MidiMessage::Controller { controller: u7::new(controller), value: u7::new(value) }
+	}
+}
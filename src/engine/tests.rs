@@ -0,0 +1,1269 @@
+mod sounding_notes {
+	use midly::{num::{u4, u7}, MidiMessage};
+
+	use super::super::{NoteRelease, SoundingNotes};
+	use crate::score::MidiEvent;
+
+	fn note_on(channel:u8, key:u8) -> MidiEvent {
+		MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(100) } }
+	}
+	fn note_off(channel:u8, key:u8) -> MidiEvent {
+		MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOff { key: u7::new(key), vel: u7::new(0) } }
+	}
+	fn controller(channel:u8, controller:u8, value:u8) -> MidiEvent {
+		MidiEvent { channel: u4::new(channel), message: MidiMessage::Controller { controller: u7::new(controller), value: u7::new(value) } }
+	}
+
+	#[test]
+	pub fn releases_only_sounding_notes() {
+		let mut sounding_notes = SoundingNotes::default();
+		sounding_notes.register(0, u4::new(0), &note_on(0, 60));
+		sounding_notes.register(0, u4::new(0), &note_on(0, 64));
+		sounding_notes.register(1, u4::new(9), &note_on(9, 36));
+		sounding_notes.register(0, u4::new(0), &note_off(0, 64));
+
+		assert_eq!(
+			sounding_notes.release(NoteRelease::default()),
+			[
+				(0, note_off(0, 60)),
+				(1, note_off(9, 36)),
+			]
+		);
+		assert_eq!(sounding_notes.release(NoteRelease::default()), []);
+	}
+
+	#[test]
+	pub fn note_on_with_zero_velocity_ends_note() {
+		let mut sounding_notes = SoundingNotes::default();
+		sounding_notes.register(0, u4::new(0), &note_on(0, 60));
+		sounding_notes.register(0, u4::new(0), &MidiEvent { channel: u4::new(0), message: MidiMessage::NoteOn { key: u7::new(60), vel: u7::new(0) } });
+
+		assert_eq!(sounding_notes.release(NoteRelease::default()), []);
+	}
+
+	#[test]
+	pub fn release_all() {
+		let mut sounding_notes = SoundingNotes::default();
+		sounding_notes.register(0, u4::new(0), &note_on(0, 60));
+		sounding_notes.register(1, u4::new(0), &note_on(0, 62));
+		sounding_notes.register(1, u4::new(0), &controller(0, 64, 127));
+
+		assert_eq!(
+			sounding_notes.release(NoteRelease::all()),
+			[
+				(1, controller(0, 64, 0)),
+				(0, note_off(0, 60)),
+				(1, note_off(0, 62)),
+				(0, controller(0, 123, 0)),
+			]
+		);
+	}
+
+	#[test]
+	pub fn release_matching() {
+		let mut sounding_notes = SoundingNotes::default();
+		sounding_notes.register(0, u4::new(0), &note_on(0, 60));
+		sounding_notes.register(1, u4::new(0), &note_on(0, 62));
+		sounding_notes.register(1, u4::new(1), &note_on(1, 64));
+		sounding_notes.register(1, u4::new(1), &controller(1, 64, 127));
+
+		// (channel 0 is still in use by track 0, so only channel 1 is sent channel-wide messages)
+		assert_eq!(
+			sounding_notes.release_matching(NoteRelease::all(), |track_index, _channel| track_index == 1),
+			[
+				(1, controller(1, 64, 0)),
+				(1, note_off(0, 62)),
+				(1, note_off(1, 64)),
+				(1, controller(1, 123, 0)),
+			]
+		);
+		assert_eq!(
+			sounding_notes.release(NoteRelease::all()),
+			[
+				(0, note_off(0, 60)),
+				(0, controller(0, 123, 0)),
+			]
+		);
+	}
+
+	#[test]
+	pub fn release_none() {
+		let mut sounding_notes = SoundingNotes::default();
+		sounding_notes.register(0, u4::new(0), &note_on(0, 60));
+
+		assert_eq!(sounding_notes.release(NoteRelease::none()), []);
+	}
+}
+
+mod transpose {
+	use midly::{num::{u4, u7}, MidiMessage};
+
+	use super::super::{OutOfRangeNotes, Transpose, TransposedNotes};
+	use crate::score::MidiEvent;
+
+	fn note_on(channel:u8, key:u8) -> MidiEvent {
+		MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(100) } }
+	}
+	fn note_off(channel:u8, key:u8) -> MidiEvent {
+		MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOff { key: u7::new(key), vel: u7::new(0) } }
+	}
+	fn transpose(semitones:i8) -> Transpose {
+		Transpose { semitones, ..Transpose::default() }
+	}
+
+	#[test]
+	pub fn note_offs_follow_their_note_ons() {
+		let mut transposed_notes = TransposedNotes::default();
+		assert_eq!(transposed_notes.apply(&transpose(2), 0, note_on(0, 60)), Some(note_on(0, 62)));
+		assert_eq!(transposed_notes.apply(&transpose(5), 0, note_on(0, 60)), Some(note_on(0, 65)));
+		assert_eq!(
+			transposed_notes.apply(&transpose(5), 0, MidiEvent { channel: u4::new(0), message: MidiMessage::Aftertouch { key: u7::new(60), vel: u7::new(50) } }),
+			Some(MidiEvent { channel: u4::new(0), message: MidiMessage::Aftertouch { key: u7::new(62), vel: u7::new(50) } })
+		);
+		assert_eq!(transposed_notes.apply(&transpose(-3), 0, note_off(0, 60)), Some(note_off(0, 62)));
+		assert_eq!(transposed_notes.apply(&transpose(-3), 0, note_off(0, 60)), Some(note_off(0, 65)));
+
+		// (notes the record knows nothing of use the current transposition)
+		assert_eq!(transposed_notes.apply(&transpose(-3), 0, note_off(0, 60)), Some(note_off(0, 57)));
+	}
+
+	#[test]
+	pub fn drums_are_left_alone() {
+		let mut transposed_notes = TransposedNotes::default();
+		assert_eq!(transposed_notes.apply(&transpose(2), 0, note_on(9, 36)), Some(note_on(9, 36)));
+
+		let transpose = Transpose { drums: true, ..transpose(2) };
+		assert_eq!(transposed_notes.apply(&transpose, 0, note_on(9, 36)), Some(note_on(9, 38)));
+	}
+
+	#[test]
+	pub fn tracks_and_channels() {
+		let mut transpose = transpose(1);
+		transpose.tracks.insert(1, 2);
+		transpose.channels.insert(u4::new(3), 4);
+
+		let mut transposed_notes = TransposedNotes::default();
+		assert_eq!(transposed_notes.apply(&transpose, 0, note_on(0, 60)), Some(note_on(0, 61)));
+		assert_eq!(transposed_notes.apply(&transpose, 1, note_on(0, 60)), Some(note_on(0, 63)));
+		assert_eq!(transposed_notes.apply(&transpose, 1, note_on(3, 60)), Some(note_on(3, 67)));
+	}
+
+	#[test]
+	pub fn out_of_range_notes() {
+		let mut transposed_notes = TransposedNotes::default();
+		assert_eq!(transposed_notes.apply(&transpose(12), 0, note_on(0, 120)), None);
+		assert_eq!(transposed_notes.apply(&transpose(0), 0, note_off(0, 120)), None);
+
+		let transpose = Transpose { out_of_range_notes: OutOfRangeNotes::Clamp, ..transpose(-12) };
+		assert_eq!(transposed_notes.apply(&transpose, 0, note_on(0, 5)), Some(note_on(0, 0)));
+		assert_eq!(transposed_notes.apply(&transpose, 0, note_off(0, 5)), Some(note_off(0, 0)));
+	}
+}
+
+mod velocity {
+	use midly::{num::{u4, u7}, MidiMessage};
+
+	use super::super::{ChannelVolumes, Velocity, VelocitySettings, VelocityTarget};
+	use crate::score::MidiEvent;
+
+	fn note_on(velocity:u8) -> MidiEvent {
+		MidiEvent { channel: u4::new(0), message: MidiMessage::NoteOn { key: u7::new(60), vel: u7::new(velocity) } }
+	}
+	fn controller(controller:u8, value:u8) -> MidiEvent {
+		MidiEvent { channel: u4::new(0), message: MidiMessage::Controller { controller: u7::new(controller), value: u7::new(value) } }
+	}
+	fn global(velocity:Velocity) -> VelocitySettings {
+		VelocitySettings { global: velocity, ..VelocitySettings::default() }
+	}
+
+	#[test]
+	pub fn transforms() {
+		assert_eq!(global(Velocity::default()).apply_to(0, note_on(100)), note_on(100));
+		assert_eq!(global(Velocity::scaled(0.5)).apply_to(0, note_on(100)), note_on(50));
+		assert_eq!(global(Velocity::scaled(2.0)).apply_to(0, note_on(100)), note_on(127));
+		assert_eq!(global(Velocity::offset(-20)).apply_to(0, note_on(100)), note_on(80));
+		assert_eq!(global(Velocity::fixed(u7::new(90))).apply_to(0, note_on(30)), note_on(90));
+		assert_eq!(global(Velocity::curved(2.0)).apply_to(0, note_on(127)), note_on(127));
+		assert_eq!(global(Velocity::curved(2.0)).apply_to(0, note_on(64)), note_on(32));
+
+		// (notes are never silenced, as a velocity of zero would make them note offs)
+		assert_eq!(global(Velocity::scaled(0.0)).apply_to(0, note_on(100)), note_on(1));
+		assert_eq!(global(Velocity::scaled(0.0)).apply_to(0, note_on(0)), note_on(0));
+	}
+
+	#[test]
+	pub fn tracks_before_global() {
+		let mut settings = global(Velocity::offset(10));
+		settings.tracks.insert(1, Velocity::scaled(0.5));
+
+		assert_eq!(settings.apply_to(0, note_on(100)), note_on(110));
+		assert_eq!(settings.apply_to(1, note_on(100)), note_on(60));
+	}
+
+	#[test]
+	pub fn volume_instead_of_notes() {
+		let settings = global(Velocity { target: VelocityTarget::Volume, ..Velocity::scaled(0.5) });
+		assert_eq!(settings.apply_to(0, note_on(100)), note_on(100));
+		assert_eq!(settings.apply_to(0, controller(7, 100)), controller(7, 50));
+		assert_eq!(settings.apply_to(0, controller(11, 100)), controller(11, 100));
+		assert_eq!(settings.apply_to(0, controller(7, 100)), controller(7, 50));
+
+		let settings = global(Velocity { target: VelocityTarget::Expression, ..Velocity::scaled(0.5) });
+		assert_eq!(settings.apply_to(0, controller(7, 100)), controller(7, 100));
+		assert_eq!(settings.apply_to(0, controller(11, 100)), controller(11, 50));
+	}
+
+	#[test]
+	pub fn refresh_volumes() {
+		let mut channel_volumes = ChannelVolumes::default();
+		channel_volumes.register(0, &controller(7, 80));
+		channel_volumes.register(1, &note_on(100));
+
+		let old_settings = VelocitySettings::default();
+		let mut new_settings = VelocitySettings::default();
+		assert_eq!(channel_volumes.refresh(&old_settings, &new_settings), []);
+
+		// (only the tracks whose volume changes are refreshed, and with the values from the score)
+		new_settings.tracks.insert(1, Velocity { target: VelocityTarget::Volume, ..Velocity::scaled(0.5) });
+		assert_eq!(channel_volumes.refresh(&old_settings, &new_settings), [(1, controller(7, 100)), (1, controller(11, 127))]);
+
+		new_settings.global = Velocity { target: VelocityTarget::Volume, ..Velocity::scaled(0.5) };
+		assert_eq!(
+			channel_volumes.refresh(&old_settings, &new_settings),
+			[(0, controller(7, 80)), (0, controller(11, 127)), (1, controller(7, 100)), (1, controller(11, 127))]
+		);
+	}
+}
+
+mod pipeline {
+	use std::borrow::Cow;
+
+	use midly::{num::{u4, u7}, MidiMessage};
+
+	use super::super::pipeline::Pipeline;
+	use crate::{processor::{ProcessorId, RemapProcessor, TransposeProcessor}, score::{Event, MidiEvent}};
+
+	fn note_on(channel:u8, key:u8) -> Event {
+		Event::Midi(MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(100) } })
+	}
+
+	#[test]
+	pub fn runs_in_order() {
+		let mut pipeline = Pipeline::default();
+		pipeline.insert(0, ProcessorId(0), Box::new(TransposeProcessor::new(2)));
+		pipeline.insert(1, ProcessorId(1), Box::new(|_track, event| match event {
+			Event::Midi(MidiEvent { message: MidiMessage::NoteOn { key, .. }, .. }) if u8::from(key) > 61 => None,
+			event => Some(event),
+		}));
+
+		assert_eq!(pipeline.run(0, Cow::Owned(note_on(0, 58))), Some(Cow::Owned(note_on(0, 60))));
+		assert_eq!(pipeline.run(0, Cow::Owned(note_on(0, 60))), None);
+
+		assert!(pipeline.move_to(ProcessorId(1), 0));
+		assert_eq!(pipeline.get_ids(), [ProcessorId(1), ProcessorId(0)]);
+		assert_eq!(pipeline.run(0, Cow::Owned(note_on(0, 60))), Some(Cow::Owned(note_on(0, 62))));
+	}
+
+	#[test]
+	pub fn insert_and_remove() {
+		let mut pipeline = Pipeline::default();
+		let event = note_on(0, 60);
+		assert!(matches!(pipeline.run(0, Cow::Borrowed(&event)), Some(Cow::Borrowed(_))));
+
+		pipeline.insert(5, ProcessorId(0), Box::new(RemapProcessor::new()));
+		pipeline.insert(0, ProcessorId(1), Box::new(RemapProcessor::new()));
+		assert_eq!(pipeline.get_ids(), [ProcessorId(1), ProcessorId(0)]);
+
+		assert!(pipeline.remove(ProcessorId(1)));
+		assert!(!pipeline.remove(ProcessorId(1)));
+		assert!(!pipeline.move_to(ProcessorId(1), 0));
+		assert_eq!(pipeline.get_ids(), [ProcessorId(0)]);
+	}
+}
+
+mod playlist {
+	use std::sync::Arc;
+
+	use super::super::Playlist;
+	use crate::score::Score;
+
+	static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/constant_tempo.mid");
+
+	fn playlist(item_count:usize) -> Playlist {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Arc::new(Score::new(&standard_midi_file).unwrap());
+
+		let mut playlist = Playlist::new(Arc::clone(&score));
+		for _ in 1..item_count {
+			playlist.push(Arc::clone(&score));
+		}
+		playlist
+	}
+
+	fn play_through(playlist:&mut Playlist) -> Vec<usize> {
+		let mut items = vec![playlist.get_current_item()];
+		while let Some(item) = playlist.advance() {
+			items.push(item);
+		}
+		items
+	}
+
+	#[test]
+	pub fn plays_in_queued_order() {
+		let mut playlist = playlist(4);
+		assert_eq!(play_through(&mut playlist), [0, 1, 2, 3]);
+		assert_eq!(playlist.get_current_item(), 3);
+	}
+
+	#[test]
+	pub fn repeats_all() {
+		let mut playlist = playlist(3);
+		playlist.set_repeat_all(true);
+		assert_eq!(playlist.advance(), Some(1));
+		assert_eq!(playlist.advance(), Some(2));
+		assert_eq!(playlist.advance(), Some(0));
+	}
+
+	#[test]
+	pub fn selects_item() {
+		let mut playlist = playlist(4);
+		assert!(playlist.select(2));
+		assert!(!playlist.select(4));
+		assert_eq!(play_through(&mut playlist), [2, 3]);
+	}
+
+	#[test]
+	pub fn shuffle_plays_each_item_once() {
+		let mut playlist = playlist(8);
+		playlist.set_shuffle(true);
+		playlist.push(Arc::clone(playlist.get_current_score()));
+
+		let mut items = play_through(&mut playlist);
+		assert_eq!(items[0], 0);
+		items.sort_unstable();
+		assert_eq!(items, (0..9).collect::<Vec<usize>>());
+	}
+
+	#[test]
+	pub fn unshuffle_continues_from_current_item() {
+		let mut playlist = playlist(8);
+		playlist.set_shuffle(true);
+		playlist.advance();
+		let current_item = playlist.get_current_item();
+
+		playlist.set_shuffle(false);
+		assert_eq!(play_through(&mut playlist), (current_item..8).collect::<Vec<usize>>());
+	}
+}
+
+mod playback {
+	use std::{sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+	use midly::{num::{u4, u7}, MidiMessage};
+
+	use super::super::{Engine, Error, LoopRegion, NoteRelease, Playlist, Render, SoundingNotes, Transpose, Velocity, VelocitySettings, VelocityTarget, MAXIMUM_SLEEP_DURATION};
+	use crate::{
+		clock::{Clock, VirtualClock},
+		messages::{ToConsole, ToEngine},
+		notification::Notification,
+		processor::{FilterProcessor, ProcessorId, RemapProcessor},
+		score::{Chase, Event, MidiEvent, Score},
+		sink::{Sink, SinkError},
+		timer::Timer,
+		timestamp::Timestamp
+	};
+
+	static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/scarborough_fair.mid");
+
+	type Recording = Vec<(Duration, usize, Event)>;
+
+	/// A sink which records each event along with the (virtual) time it was received, and each notification.
+	struct RecordingSink {
+		clock: Arc<VirtualClock>,
+		start: Instant,
+		recording: Arc<Mutex<Recording>>,
+		timestamps: Arc<Mutex<Vec<Timestamp>>>,
+		notifications: Arc<Mutex<Vec<Notification>>>
+	}
+
+	impl Sink for RecordingSink {
+		fn receive(&mut self, track:usize, midi_event:MidiEvent) -> Result<(), SinkError> {
+			self.receive_event(track, &Event::Midi(midi_event))
+		}
+		fn receive_event(&mut self, track:usize, event:&Event) -> Result<(), SinkError> {
+			self.recording.lock().unwrap().push((self.clock.now() - self.start, track, event.clone()));
+			Ok(())
+		}
+		fn receive_timestamped(&mut self, track:usize, event:&Event, timestamp:&Timestamp) -> Result<(), SinkError> {
+			self.timestamps.lock().unwrap().push(*timestamp);
+			self.receive_event(track, event)
+		}
+		fn notify(&mut self, notification:&Notification) -> Result<(), SinkError> {
+			self.notifications.lock().unwrap().push(notification.clone());
+			Ok(())
+		}
+	}
+
+	fn score() -> Arc<Score> {
+		load(MID_FILE_DATA)
+	}
+	fn load(data:&[u8]) -> Arc<Score> {
+		let standard_midi_file = midly::Smf::parse(data).unwrap();
+		Arc::new(Score::new(&standard_midi_file).unwrap())
+	}
+
+	/// Run the engine against a virtual clock, handing it each message of the script at its time and performing until
+	/// the given time, returning what was recorded and when (if at all) the engine reported having stopped.
+	fn run(score:&Arc<Score>, script:Vec<(Duration, ToEngine)>, until:Duration) -> (Recording, Option<Duration>) {
+		let (recording, stopped_at, _timestamps, _notifications) = run_with_timestamps(score, script, until, Instant::now());
+		(recording, stopped_at)
+	}
+
+	/// As [`run`], also returning the notifications sent by the engine.
+	fn run_with_notifications(score:&Arc<Score>, script:Vec<(Duration, ToEngine)>, until:Duration) -> (Recording, Option<Duration>, Vec<Notification>) {
+		let (recording, stopped_at, _timestamps, notifications) = run_with_timestamps(score, script, until, Instant::now());
+		(recording, stopped_at, notifications)
+	}
+
+	/// As [`run`], also returning the timestamp of each event recorded and the notifications sent, with the virtual
+	/// clock starting at the given instant.
+	fn run_with_timestamps(score:&Arc<Score>, script:Vec<(Duration, ToEngine)>, until:Duration, start:Instant) -> (Recording, Option<Duration>, Vec<Timestamp>, Vec<Notification>) {
+		let clock = Arc::new(VirtualClock::new(start));
+		let recording = Arc::new(Mutex::new(vec![]));
+		let timestamps = Arc::new(Mutex::new(vec![]));
+		let notifications = Arc::new(Mutex::new(vec![]));
+		let sink = RecordingSink {
+			clock: Arc::clone(&clock),
+			start,
+			recording: Arc::clone(&recording),
+			timestamps: Arc::clone(&timestamps),
+			notifications: Arc::clone(&notifications)
+		};
+		let (channel_to_console, channel_from_engine) = crossbeam_channel::unbounded::<ToConsole>();
+		let timer = Timer::new(score.get_timing(), MAXIMUM_SLEEP_DURATION).unwrap();
+		let mut engine = Engine::new(channel_to_console, Box::new(sink), Box::new(Arc::clone(&clock)), Playlist::new(Arc::clone(score)), timer);
+
+		let mut stopped_at = None;
+		let mut perform_until = |engine:&mut Engine, time:Duration| {
+			while let Some(deadline) = engine.deadline.filter(|deadline| *deadline <= start + time) {
+				clock.advance_to(deadline);
+				engine.perform().unwrap();
+				if channel_from_engine.try_iter().any(|message| matches!(message, ToConsole::Stopped)) {
+					stopped_at = Some(clock.now() - start);
+				}
+			}
+			clock.advance_to(start + time);
+		};
+
+		for (time, message) in script {
+			perform_until(&mut engine, time);
+			engine.handle(message).unwrap();
+		}
+		perform_until(&mut engine, until);
+
+		drop(engine);
+		let recording = recording.lock().unwrap().clone();
+		let timestamps = timestamps.lock().unwrap().clone();
+		let notifications = notifications.lock().unwrap().clone();
+		(recording, stopped_at, timestamps, notifications)
+	}
+
+	/// Every event of the score from the given tick onwards, along with when it should be heard
+	/// (counting from the given tick).
+	fn expected_from(score:&Score, from:usize) -> Recording {
+		let offset = score.calculate_duration_until(1.0, from);
+		let mut expected = vec![];
+		let mut index = from;
+		while let Some(simultaneous_events_per_track) = score.gather_all_events_for_index(index) {
+			for (track_index, simultaneous_events) in simultaneous_events_per_track {
+				for event in &simultaneous_events.events {
+					expected.push((score.calculate_duration_until(1.0, index) - offset, track_index, event.clone()));
+				}
+			}
+			index += score.calculate_ticks_until_next_events_from_index(index).unwrap_or(1);
+		}
+		expected
+	}
+
+	fn assert_close(actual:Duration, expected:Duration) {
+		let difference = actual.abs_diff(expected);
+		assert!(difference < Duration::from_millis(1), "{actual:?} is not close to {expected:?}");
+	}
+
+	fn assert_recording_matches(actual:&[(Duration, usize, Event)], expected:&[(Duration, usize, Event)]) {
+		assert_eq!(actual.len(), expected.len());
+		for ((actual_time, actual_track, actual_event), (expected_time, expected_track, expected_event)) in actual.iter().zip(expected) {
+			assert_eq!((actual_track, actual_event), (expected_track, expected_event));
+			assert_close(*actual_time, *expected_time);
+		}
+	}
+
+	#[test]
+	pub fn plays_in_time() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+
+		let (recording, stopped_at) = run(&score, vec![(Duration::ZERO, ToEngine::Play)], length * 2);
+
+		assert_recording_matches(&recording, &expected_from(&score, 0));
+		assert_close(stopped_at.unwrap(), length);
+	}
+
+	#[test]
+	pub fn looping() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+
+		let (recording, stopped_at) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::SetLooping(true)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 2 + length / 2
+		);
+
+		let once = expected_from(&score, 0);
+		let mut expected = once.clone();
+		expected.extend(once.iter().cloned().map(|(time, track, event)| (time + length, track, event)));
+		expected.extend(once.iter().filter(|(time, _, _)| *time <= length / 2).cloned().map(|(time, track, event)| (time + length * 2, track, event)));
+
+		assert_recording_matches(&recording, &expected);
+		assert_eq!(stopped_at, None);
+	}
+
+	/// Every event of the score from the first tick onwards, up to (but not including) the second.
+	fn expected_between(score:&Score, from:usize, to:usize) -> Recording {
+		let end = score.calculate_duration_until(1.0, to) - score.calculate_duration_until(1.0, from);
+		expected_from(score, from).into_iter().filter(|(time, _, _)| *time < end).collect()
+	}
+
+	fn delayed(recording:&[(Duration, usize, Event)], delay:Duration) -> impl Iterator<Item = (Duration, usize, Event)> + '_ {
+		recording.iter().cloned().map(move |(time, track, event)| (time + delay, track, event))
+	}
+
+	#[test]
+	pub fn loop_region() {
+		// (the region begins and ends at different tempos, so the tempo must be restored on returning to its start)
+		let score = load(include_bytes!("../../test_midi_files/changing_tempo.mid"));
+		let (start, end) = (4800, 8160);
+		let until_start = score.calculate_duration_until(1.0, start);
+		let until_end = score.calculate_duration_until(1.0, end);
+		let region_length = until_end - until_start;
+
+		let (recording, stopped_at, notifications) = run_with_notifications(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::SetChase(Chase::none())),
+				(Duration::ZERO, ToEngine::SetLoopRegion(Some(LoopRegion::new(start, end, Some(2))))),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			score.calculate_duration(1.0) * 3
+		);
+
+		// (the region is played once, repeated twice, then playback continues past its end)
+		let mut expected = expected_between(&score, 0, end);
+		expected.extend(delayed(&expected_between(&score, start, end), until_end));
+		expected.extend(delayed(&expected_from(&score, start), until_end + region_length));
+		assert_recording_matches(&recording, &expected);
+		assert_close(stopped_at.unwrap(), until_end + region_length + (score.calculate_duration(1.0) - until_start));
+
+		let wraps:Vec<usize> = notifications
+			.iter()
+			.filter_map(|notification| if let Notification::LoopWrapped(count) = notification { Some(*count) } else { None })
+			.collect();
+		assert_eq!(wraps, [1, 2]);
+	}
+
+	#[test]
+	pub fn loop_region_chases_state() {
+		let score = load(include_bytes!("../../test_midi_files/channel_state.mid"));
+		let (start, end) = (193, score.len() - 1);
+		let until_end = score.calculate_duration_until(1.0, end);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::SetChase(Chase::all())),
+				(Duration::ZERO, ToEngine::SetLoopRegion(Some(LoopRegion::new(start, end, Some(1))))),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			score.calculate_duration(1.0) * 3
+		);
+
+		// (on returning to the start, the controllers, programs, etc. in effect there are sent first)
+		let chased:Recording = score
+			.gather_chase_events_until(start, Chase::all())
+			.into_iter()
+			.map(|(track, midi_event)| (until_end, track, Event::Midi(midi_event)))
+			.collect();
+		assert!(!chased.is_empty());
+		let first_wrapped = recording.iter().position(|(time, _, _)| *time + Duration::from_millis(1) > until_end).unwrap();
+		assert_recording_matches(&recording[first_wrapped..first_wrapped + chased.len()], &chased);
+	}
+
+	#[test]
+	pub fn loop_region_set_while_waiting() {
+		let score = score();
+		let expected = expected_from(&score, 0);
+
+		// (set the region just after performing a tick, once the engine has already stepped on past its end)
+		let mut position = 0;
+		while score.calculate_ticks_until_next_events_from_index(position).unwrap() < 2 {
+			position += score.calculate_ticks_until_next_events_from_index(position).unwrap();
+		}
+		let performed = score.calculate_duration_until(1.0, position);
+
+		let (recording, _, notifications) = run_with_notifications(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::SetChase(Chase::none())),
+				(Duration::ZERO, ToEngine::Play),
+				(performed, ToEngine::SetLoopRegion(Some(LoopRegion::new(0, position + 1, Some(1))))),
+			],
+			score.calculate_duration(1.0) * 3
+		);
+
+		assert!(notifications.contains(&Notification::LoopWrapped(1)));
+		let events:Vec<&Event> = recording.iter().map(|(_, _, event)| event).collect();
+		let expected_events:Vec<&Event> = expected
+			.iter()
+			.filter(|(time, _, _)| *time <= performed)
+			.chain(&expected)
+			.map(|(_, _, event)| event)
+			.collect();
+		assert_eq!(events, expected_events);
+	}
+
+	#[test]
+	pub fn playlist_without_gaps() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+
+		let (recording, stopped_at) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::Enqueue(Arc::clone(&score))),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 3
+		);
+
+		let once = expected_from(&score, 0);
+		let mut expected = once.clone();
+		expected.extend(once.into_iter().map(|(time, track, event)| (time + length, track, event)));
+
+		assert_recording_matches(&recording, &expected);
+		assert_close(stopped_at.unwrap(), length * 2);
+	}
+
+	#[test]
+	pub fn jump_to() {
+		let score = score();
+		let middle = score.len() / 2;
+		let remaining_length = score.calculate_duration(1.0) - score.calculate_duration_until(1.0, middle);
+
+		let (recording, stopped_at) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::JumpTo(middle)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			remaining_length * 2
+		);
+
+		assert_recording_matches(&recording, &expected_from(&score, middle));
+		assert_close(stopped_at.unwrap(), remaining_length);
+	}
+
+	#[test]
+	pub fn jump_while_playing() {
+		let score = score();
+		let middle = score.len() / 2;
+		let second = Duration::from_secs(1);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::Play),
+				(second, ToEngine::JumpTo(middle)),
+			],
+			second * 3
+		);
+
+		let mut expected:Recording = expected_from(&score, 0).into_iter().filter(|(time, _, _)| *time < second).collect();
+		expected.extend(
+			expected_from(&score, middle)
+				.into_iter()
+				.filter(|(time, _, _)| *time <= second * 2)
+				.map(|(time, track, event)| (time + second, track, event))
+		);
+
+		assert_recording_matches(&recording, &expected);
+	}
+
+	#[test]
+	pub fn speed_change() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let change = Duration::from_secs(5);
+
+		let (recording, stopped_at) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::Play),
+				(change, ToEngine::SetSpeed(2.0)),
+			],
+			length
+		);
+
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.map(|(time, track, event)| {
+				if time < change {
+					(time, track, event)
+				} else {
+					(change + (time - change) / 2, track, event)
+				}
+			})
+			.collect();
+
+		assert_recording_matches(&recording, &expected);
+		assert_close(stopped_at.unwrap(), change + (length - change) / 2);
+	}
+
+	#[test]
+	pub fn speed_change_while_paused() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let pause = Duration::from_millis(2_250);
+		let change = Duration::from_millis(2_500);
+		let resume = Duration::from_secs(3);
+
+		let (recording, stopped_at) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::Play),
+				(pause, ToEngine::Pause),
+				(change, ToEngine::SetSpeed(2.0)),
+				(resume, ToEngine::Play),
+			],
+			length * 2
+		);
+
+		// (including the time left until the event that was next when paused)
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.map(|(time, track, event)| {
+				if time < pause {
+					(time, track, event)
+				} else {
+					(resume + (time - pause) / 2, track, event)
+				}
+			})
+			.collect();
+
+		assert_recording_matches(&recording, &expected);
+		assert_close(stopped_at.unwrap(), resume + (length - pause) / 2);
+	}
+
+	#[test]
+	pub fn pause_and_resume() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let pause = Duration::from_secs(2);
+		let resume = Duration::from_secs(3);
+
+		let (recording, stopped_at) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetNoteRelease(NoteRelease::none())),
+				(Duration::ZERO, ToEngine::Play),
+				(pause, ToEngine::Pause),
+				(resume, ToEngine::Play),
+			],
+			length * 2
+		);
+
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.map(|(time, track, event)| {
+				if time < pause {
+					(time, track, event)
+				} else {
+					(time + (resume - pause), track, event)
+				}
+			})
+			.collect();
+
+		assert_recording_matches(&recording, &expected);
+		assert_close(stopped_at.unwrap(), length + (resume - pause));
+	}
+
+	#[test]
+	pub fn render_matches_playback() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+
+		let (recording, _) = run(&score, vec![(Duration::ZERO, ToEngine::Play)], length * 2);
+		let rendering:Recording = Render::new(&[Arc::clone(&score)], vec![ToEngine::Play]).unwrap().collect();
+
+		assert_eq!(rendering, recording);
+	}
+
+	#[test]
+	pub fn render_with_settings() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let middle = score.len() / 2;
+		let settings = || vec![
+			ToEngine::SetSpeed(1.5),
+			ToEngine::SetLooping(true),
+			ToEngine::SetNoteRelease(NoteRelease::all()),
+			ToEngine::JumpTo(middle),
+			ToEngine::Play,
+		];
+
+		let (recording, stopped_at) = run(
+			&score,
+			settings().into_iter().map(|message| (Duration::ZERO, message)).collect(),
+			length * 2
+		);
+		let rendering:Recording = Render::new(&[Arc::clone(&score)], settings())
+			.unwrap()
+			.take_while(|(time, _, _)| *time <= length * 2)
+			.collect();
+
+		assert_eq!(rendering, recording);
+		assert_eq!(stopped_at, None);
+	}
+
+	#[test]
+	pub fn render_nothing() {
+		assert!(matches!(Render::new(&[], vec![ToEngine::Play]), Err(Error::EmptyPlaylist)));
+
+		let mut render = Render::new(&[score()], vec![ToEngine::Play]).unwrap();
+		assert!(render.by_ref().count() > 0);
+		assert!(render.error().is_none());
+	}
+
+	#[test]
+	pub fn timestamps() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let start = Instant::now();
+
+		let (recording, _, timestamps, _) = run_with_timestamps(&score, vec![(Duration::ZERO, ToEngine::Play)], length * 2, start);
+
+		assert_eq!(recording.len(), timestamps.len());
+		for ((time, _, _), timestamp) in recording.iter().zip(&timestamps) {
+			assert_eq!(timestamp.instant - start, *time);
+			assert_eq!(timestamp.time, score.calculate_duration_until(1.0, timestamp.tick));
+			assert_close(timestamp.time, *time);
+		}
+	}
+
+	#[test]
+	pub fn lookahead() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let lookahead = Duration::from_millis(100);
+		let start = Instant::now();
+
+		let (recording, _, timestamps, _) = run_with_timestamps(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SetLookahead(lookahead)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 2,
+			start
+		);
+
+		// (events in the first moments of playback can only be sent straight away)
+		let expected = expected_from(&score, 0);
+		assert_eq!(recording.len(), expected.len());
+		for (((time, _, _), timestamp), (expected_time, _, _)) in recording.iter().zip(&timestamps).zip(&expected) {
+			assert_close(timestamp.instant - start, *expected_time);
+			assert_close(*time, expected_time.saturating_sub(lookahead));
+		}
+	}
+
+	fn is_note_on(event:&Event) -> bool {
+		matches!(event, Event::Midi(MidiEvent { message: MidiMessage::NoteOn { vel, .. }, .. }) if u8::from(*vel) > 0)
+	}
+
+	/// The index of the track with the most notes.
+	fn busiest_track(score:&Score) -> usize {
+		let expected = expected_from(score, 0);
+		(0..score.get_track_count())
+			.max_by_key(|track| expected.iter().filter(|(_, track_index, event)| track_index == track && is_note_on(event)).count())
+			.unwrap()
+	}
+
+	#[test]
+	pub fn mute_track() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let muted_track = busiest_track(&score);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::MuteTrack(muted_track, true)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 2
+		);
+
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.filter(|(_, track_index, event)| *track_index != muted_track || !is_note_on(event))
+			.collect();
+		assert_recording_matches(&recording, &expected);
+	}
+
+	#[test]
+	pub fn solo_track() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let soloed_track = busiest_track(&score);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SoloTrack(soloed_track, true)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 2
+		);
+
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.filter(|(_, track_index, event)| *track_index == soloed_track || !is_note_on(event))
+			.collect();
+		assert_recording_matches(&recording, &expected);
+	}
+
+	#[test]
+	pub fn mute_track_while_playing() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let muted_track = busiest_track(&score);
+		let middle = length / 2;
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::Play),
+				(middle, ToEngine::MuteTrack(muted_track, true)),
+				(middle + length / 4, ToEngine::MuteTrack(muted_track, false)),
+			],
+			length * 2
+		);
+
+		// (no notes are started while muted, and every note started is released)
+		let muted_notes = recording
+			.iter()
+			.filter(|(time, track_index, event)| *track_index == muted_track && (middle..middle + length / 4).contains(time) && is_note_on(event));
+		assert_eq!(muted_notes.count(), 0);
+
+		let mut sounding_notes = SoundingNotes::default();
+		for (_, track_index, event) in &recording {
+			if let Event::Midi(midi_event) = event {
+				sounding_notes.register(*track_index, midi_event.channel, midi_event);
+			}
+		}
+		assert_eq!(sounding_notes.release(NoteRelease::default()), []);
+
+		// (the notes sounding at the moment of muting are released straight away)
+		let released_at_middle = recording
+			.iter()
+			.filter(|(time, track_index, event)| *time == middle && *track_index == muted_track && matches!(event, Event::Midi(MidiEvent { message: MidiMessage::NoteOff { .. }, .. })))
+			.count();
+		assert!(released_at_middle > 0);
+	}
+
+	/// The channel with the most notes.
+	fn busiest_channel(score:&Score) -> u4 {
+		let expected = expected_from(score, 0);
+		(0..16)
+			.map(u4::new)
+			.max_by_key(|channel| expected.iter().filter(|(_, _, event)| matches!(event, Event::Midi(midi_event) if midi_event.channel == *channel) && is_note_on(event)).count())
+			.unwrap()
+	}
+
+	#[test]
+	pub fn mute_and_solo_channels() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let channel = busiest_channel(&score);
+		let is_on_channel = |event:&Event| matches!(event, Event::Midi(midi_event) if midi_event.channel == channel);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::MuteChannel(channel, true)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 2
+		);
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.filter(|(_, _, event)| !(is_on_channel(event) && is_note_on(event)))
+			.collect();
+		assert_recording_matches(&recording, &expected);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::SoloChannel(channel, true)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 2
+		);
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.filter(|(_, _, event)| is_on_channel(event) || !is_note_on(event))
+			.collect();
+		assert_recording_matches(&recording, &expected);
+	}
+
+	#[test]
+	pub fn remap_channel() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let channel = busiest_channel(&score);
+		let destination = u4::new(15);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::RemapChannel(channel, destination)),
+				(Duration::ZERO, ToEngine::Play),
+			],
+			length * 2
+		);
+
+		// (every kind of channel message is moved)
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.map(|(time, track_index, event)| match event {
+				Event::Midi(midi_event) if midi_event.channel == channel => (time, track_index, Event::Midi(MidiEvent { channel: destination, ..midi_event })),
+				event => (time, track_index, event),
+			})
+			.collect();
+		assert_recording_matches(&recording, &expected);
+	}
+
+	#[test]
+	pub fn remap_channel_while_playing() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let channel = busiest_channel(&score);
+		let middle = length / 2;
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::Play),
+				(middle, ToEngine::RemapChannel(channel, u4::new(15))),
+			],
+			length * 2
+		);
+
+		// (the notes left on the old channel are released, so nothing is left hanging)
+		let mut sounding_notes = SoundingNotes::default();
+		for (_, track_index, event) in &recording {
+			if let Event::Midi(midi_event) = event {
+				sounding_notes.register(*track_index, midi_event.channel, midi_event);
+			}
+		}
+		assert_eq!(sounding_notes.release(NoteRelease::default()), []);
+		assert!(recording.iter().any(|(time, _, event)| *time == middle && matches!(event, Event::Midi(MidiEvent { channel: released_channel, message: MidiMessage::NoteOff { .. } }) if *released_channel == channel)));
+	}
+
+	#[test]
+	pub fn mute_channel_remapped_with_another() {
+		// (one track holding a note on each of channels 0 and 1 for two seconds)
+		let note = |delta:u32, channel:u8, key:u8, vel:u8| midly::TrackEvent {
+			delta: midly::num::u28::new(delta),
+			kind: midly::TrackEventKind::Midi { channel: u4::new(channel), message: MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(vel) } }
+		};
+		let standard_midi_file = midly::Smf {
+			header: midly::Header::new(midly::Format::SingleTrack, midly::Timing::Metrical(midly::num::u15::new(96))),
+			tracks: vec![vec![
+				midly::TrackEvent { delta: midly::num::u28::new(0), kind: midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(midly::num::u24::new(500_000))) },
+				note(0, 0, 60, 100),
+				note(0, 1, 64, 100),
+				note(384, 0, 60, 0),
+				note(0, 1, 64, 0),
+			]],
+		};
+		let score = Arc::new(Score::new(&standard_midi_file).unwrap());
+		let destination = u4::new(5);
+		let middle = Duration::from_secs(1);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::RemapChannel(u4::new(0), destination)),
+				(Duration::ZERO, ToEngine::RemapChannel(u4::new(1), destination)),
+				(Duration::ZERO, ToEngine::Play),
+				(middle, ToEngine::MuteChannel(u4::new(0), true)),
+			],
+			Duration::from_secs(3)
+		);
+
+		// (only the note from the muted channel is released, leaving the other sounding on the shared destination)
+		let released:Vec<_> = recording.iter().filter(|(time, _, _)| *time == middle).map(|(_, _, event)| event.clone()).collect();
+		assert_eq!(
+			released,
+			[Event::Midi(MidiEvent { channel: destination, message: MidiMessage::NoteOff { key: u7::new(60), vel: u7::new(0) } })]
+		);
+	}
+
+	#[test]
+	pub fn transpose_while_playing() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let middle = length / 2;
+		let transpose = |semitones| ToEngine::SetTranspose(Transpose { semitones, ..Transpose::default() });
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, transpose(3)),
+				(Duration::ZERO, ToEngine::Play),
+				(middle, transpose(-2)),
+			],
+			length * 2
+		);
+
+		// (notes are shifted by the transposition in effect when they begin, and ended where they began)
+		let expected = expected_from(&score, 0);
+		assert_eq!(recording.len(), expected.len());
+		let mut sounding_notes = SoundingNotes::default();
+		for ((time, track_index, event), (_, _, expected_event)) in recording.iter().zip(&expected) {
+			if let (Event::Midi(midi_event), Event::Midi(expected_midi_event)) = (event, expected_event) {
+				if let (MidiMessage::NoteOn { key, vel }, MidiMessage::NoteOn { key: expected_key, .. }) = (midi_event.message, expected_midi_event.message) {
+					if u8::from(vel) > 0 && u8::from(midi_event.channel) != 9 {
+						let semitones = if *time < middle { 3 } else { -2 };
+						assert_eq!(i16::from(u8::from(key)), i16::from(u8::from(expected_key)) + semitones);
+					}
+				}
+				sounding_notes.register(*track_index, midi_event.channel, midi_event);
+			}
+		}
+		assert_eq!(sounding_notes.release(NoteRelease::default()), []);
+	}
+
+	#[test]
+	pub fn velocity() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let middle = length / 2;
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::Play),
+				(middle, ToEngine::SetVelocity(VelocitySettings { global: Velocity::scaled(0.5), ..VelocitySettings::default() })),
+			],
+			length * 2
+		);
+
+		let expected = expected_from(&score, 0);
+		assert_eq!(recording.len(), expected.len());
+		for ((time, _, event), (_, _, expected_event)) in recording.iter().zip(&expected) {
+			if let (Event::Midi(MidiEvent { message: MidiMessage::NoteOn { vel, .. }, .. }), Event::Midi(MidiEvent { message: MidiMessage::NoteOn { vel: expected_vel, .. }, .. })) = (event, expected_event) {
+				if *time < middle || u8::from(*expected_vel) == 0 {
+					assert_eq!(vel, expected_vel);
+				} else {
+					assert_eq!(u8::from(*vel), (f32::from(u8::from(*expected_vel)) * 0.5).round().max(1.0) as u8);
+				}
+			}
+		}
+	}
+
+	#[test]
+	pub fn volume_changes_straight_away() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let middle = length / 2;
+		let is_volume = |event:&Event| matches!(event, Event::Midi(MidiEvent { message: MidiMessage::Controller { controller, .. }, .. }) if u8::from(*controller) == 7);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::Play),
+				(middle, ToEngine::SetVelocity(VelocitySettings { global: Velocity { target: VelocityTarget::Volume, ..Velocity::scaled(0.5) }, ..VelocitySettings::default() })),
+			],
+			length * 2
+		);
+
+		assert!(recording.iter().any(|(time, _, event)| *time == middle && is_volume(event)));
+	}
+
+	#[test]
+	pub fn processors() {
+		let score = score();
+		let length = score.calculate_duration(1.0);
+		let middle = length / 2;
+		let channel = busiest_channel(&score);
+		let destination = u4::new(15);
+		let is_on_channel = |event:&Event| matches!(event, Event::Midi(midi_event) if midi_event.channel == channel);
+
+		let (recording, _) = run(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::InsertProcessor(0, ProcessorId(0), Box::new(FilterProcessor::channels(&[channel])))),
+				(Duration::ZERO, ToEngine::InsertProcessor(1, ProcessorId(1), Box::new(RemapProcessor::new().remap(channel, destination)))),
+				(Duration::ZERO, ToEngine::Play),
+				(middle, ToEngine::RemoveProcessor(ProcessorId(0))),
+			],
+			length * 2
+		);
+
+		// (only the selected channel is heard, moved, until the filter is removed)
+		let remapped = |event:&Event| match event {
+			Event::Midi(midi_event) if midi_event.channel == channel => Event::Midi(MidiEvent { channel: destination, ..*midi_event }),
+			event => event.clone(),
+		};
+		let expected:Recording = expected_from(&score, 0)
+			.into_iter()
+			.filter(|(time, _, event)| *time >= middle || is_on_channel(event) || !matches!(event, Event::Midi(_)))
+			.map(|(time, track_index, event)| (time, track_index, remapped(&event)))
+			.collect();
+		let performed:Recording = recording
+			.into_iter()
+			.filter(|(time, _, event)| *time != middle || !matches!(event, Event::Midi(MidiEvent { message: MidiMessage::NoteOff { .. }, .. })))
+			.collect();
+		assert_recording_matches(&performed, &expected);
+	}
+
+	#[test]
+	pub fn no_such_processor() {
+		let score = score();
+
+		let (_, _, notifications) = run_with_notifications(
+			&score,
+			vec![
+				(Duration::ZERO, ToEngine::InsertProcessor(0, ProcessorId(0), Box::new(RemapProcessor::new()))),
+				(Duration::ZERO, ToEngine::RemoveProcessor(ProcessorId(1))),
+				(Duration::ZERO, ToEngine::MoveProcessor(ProcessorId(2), 0)),
+				(Duration::ZERO, ToEngine::RemoveProcessor(ProcessorId(0))),
+			],
+			Duration::from_secs(1)
+		);
+
+		assert_eq!(notifications, [Notification::NoSuchProcessor(ProcessorId(1)), Notification::NoSuchProcessor(ProcessorId(2))]);
+	}
+}
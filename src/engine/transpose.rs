@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use midly::{num::{u4, u7}, MidiMessage};
+
+use crate::score::MidiEvent;
+
+const DRUM_CHANNEL:u8 = 9;
+
+/// What to do with notes transposed beyond the range of MIDI keys (0 to 127).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OutOfRangeNotes {
+	/// Leave the notes out.
+	#[default]
+	Drop,
+	/// Play the notes at the nearest key in range instead.
+	Clamp,
+}
+
+/// How far (in semitones) the notes of the score are shifted. The global, per-track and per-channel transpositions
+/// are added together.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Transpose {
+	pub semitones: i8,
+	pub tracks: BTreeMap<usize, i8>,
+	pub channels: BTreeMap<u4, i8>,
+	/// Whether to transpose the drum channel (channel 10) too, where keys select instruments rather than pitches.
+	pub drums: bool,
+	pub out_of_range_notes: OutOfRangeNotes,
+}
+
+impl Transpose {
+	/// The key a note on the given track and channel is moved to, or `None` if it is to be left out.
+	fn transpose(&self, track_index:usize, channel:u4, key:u7) -> Option<u7> {
+		if u8::from(channel) == DRUM_CHANNEL && !self.drums {
+			return Some(key);
+		}
+
+		let semitones = i16::from(self.semitones)
+			+ i16::from(self.tracks.get(&track_index).copied().unwrap_or(0))
+			+ i16::from(self.channels.get(&channel).copied().unwrap_or(0));
+		let transposed_key = i16::from(u8::from(key)) + semitones;
+
+		match u8::try_from(transposed_key) {
+			Ok(transposed_key) if transposed_key <= 127 => Some(u7::new(transposed_key)),
+			_ => match self.out_of_range_notes {
+				OutOfRangeNotes::Drop => None,
+				OutOfRangeNotes::Clamp => Some(u7::new(transposed_key.clamp(0, 127) as u8)),
+			},
+		}
+	}
+}
+
+/// A record of the key each sounding note was transposed to when it began, so that its note off (and any
+/// aftertouch) follows it there, even if the transposition has since changed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransposedNotes {
+	// (track index, channel, key in the score) > keys played, oldest first (or `None` for notes left out)
+	notes: BTreeMap<(usize, u4, u7), VecDeque<Option<u7>>>,
+}
+
+impl TransposedNotes {
+	/// Transpose a [`MidiEvent`] from the given track, returning `None` if it is to be left out.
+	pub fn apply(&mut self, transpose:&Transpose, track_index:usize, midi_event:MidiEvent) -> Option<MidiEvent> {
+		let channel = midi_event.channel;
+		let message = match midi_event.message {
+			MidiMessage::NoteOn { key, vel } if u8::from(vel) > 0 => {
+				let transposed_key = transpose.transpose(track_index, channel, key);
+				self.notes.entry((track_index, channel, key)).or_default().push_back(transposed_key);
+				MidiMessage::NoteOn { key: transposed_key?, vel }
+			},
+			MidiMessage::NoteOn { key, vel } => MidiMessage::NoteOn { key: self.end(transpose, track_index, channel, key)?, vel },
+			MidiMessage::NoteOff { key, vel } => MidiMessage::NoteOff { key: self.end(transpose, track_index, channel, key)?, vel },
+			MidiMessage::Aftertouch { key, vel } => MidiMessage::Aftertouch { key: self.sounding(transpose, track_index, channel, key)?, vel },
+			message => message,
+		};
+
+		Some(MidiEvent { channel, message })
+	}
+
+	/// Forget every note, as they have all been released.
+	pub fn clear(&mut self) {
+		self.notes.clear();
+	}
+
+	/// The key the oldest note started from the given key was played at, which is now ending. Notes that began
+	/// before the record was last cleared use the current transposition.
+	fn end(&mut self, transpose:&Transpose, track_index:usize, channel:u4, key:u7) -> Option<u7> {
+		let Some(transposed_keys) = self.notes.get_mut(&(track_index, channel, key)) else {
+			return transpose.transpose(track_index, channel, key);
+		};
+
+		let transposed_key = transposed_keys.pop_front().flatten();
+		if transposed_keys.is_empty() {
+			self.notes.remove(&(track_index, channel, key));
+		}
+		transposed_key
+	}
+
+	/// The key the oldest sounding note started from the given key is being played at.
+	fn sounding(&self, transpose:&Transpose, track_index:usize, channel:u4, key:u7) -> Option<u7> {
+		match self.notes.get(&(track_index, channel, key)).and_then(VecDeque::front) {
+			Some(transposed_key) => *transposed_key,
+			None => transpose.transpose(track_index, channel, key),
+		}
+	}
+}
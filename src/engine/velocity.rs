@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use midly::{num::{u4, u7}, MidiMessage};
+
+use crate::score::MidiEvent;
+
+const VOLUME_CONTROLLER:u8 = 7;
+const EXPRESSION_CONTROLLER:u8 = 11;
+/// The values channels are assumed to have until the score says otherwise.
+const DEFAULT_VOLUME:u8 = 100;
+const DEFAULT_EXPRESSION:u8 = 127;
+
+/// What a [Velocity] change is applied to.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VelocityTarget {
+	/// The velocities of "Note On" messages.
+	#[default]
+	Notes,
+	/// The values of channel volume messages (controller 7), leaving the notes alone.
+	Volume,
+	/// The values of expression messages (controller 11), leaving the notes alone.
+	Expression,
+}
+
+/// A change made to the velocities of notes (or, depending on its [`VelocityTarget`], to the volume of channels) as
+/// they are played. The value is fixed (if requested), then curved, scaled and offset, in that order.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Velocity {
+	/// Replace every value with this one.
+	pub fixed: Option<u7>,
+	/// Raise values (as a proportion of 127) to this power; above 1 softens quieter values, below 1 strengthens them.
+	pub curve: f32,
+	/// Multiply values by this factor.
+	pub scale: f32,
+	/// Add this to values.
+	pub offset: i8,
+	pub target: VelocityTarget,
+}
+
+impl Default for Velocity {
+	fn default() -> Velocity {
+		Velocity {
+			fixed: None,
+			curve: 1.0,
+			scale: 1.0,
+			offset: 0,
+			target: VelocityTarget::Notes
+		}
+	}
+}
+
+impl Velocity {
+	/// A [Velocity] which multiplies velocities by the given factor.
+	pub fn scaled(scale:f32) -> Velocity {
+		Velocity { scale, ..Velocity::default() }
+	}
+
+	/// A [Velocity] which adds the given amount to velocities.
+	pub fn offset(offset:i8) -> Velocity {
+		Velocity { offset, ..Velocity::default() }
+	}
+
+	/// A [Velocity] which plays every note at the given velocity.
+	pub fn fixed(velocity:u7) -> Velocity {
+		Velocity { fixed: Some(velocity), ..Velocity::default() }
+	}
+
+	/// A [Velocity] which raises velocities (as a proportion of 127) to the given power.
+	pub fn curved(curve:f32) -> Velocity {
+		Velocity { curve, ..Velocity::default() }
+	}
+
+	/// Whether this [Velocity] changes nothing.
+	pub fn is_identity(&self) -> bool {
+		*self == Velocity { target: self.target, ..Velocity::default() }
+	}
+
+	/// Apply the change to a value, keeping the result no lower than `minimum`.
+	fn apply(&self, value:u7, minimum:u8) -> u7 {
+		let value = f32::from(u8::from(self.fixed.unwrap_or(value))) / 127.0;
+		let value = value.powf(self.curve.max(0.0)) * 127.0 * self.scale.max(0.0) + f32::from(self.offset);
+		u7::new(value.round().clamp(f32::from(minimum), 127.0) as u8)
+	}
+}
+
+/// The velocity changes for the whole score, and for individual tracks (which are applied first).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VelocitySettings {
+	pub global: Velocity,
+	pub tracks: BTreeMap<usize, Velocity>,
+}
+
+impl VelocitySettings {
+	/// The changes applied to the given track, in order.
+	fn for_track(&self, track_index:usize) -> impl Iterator<Item = &Velocity> {
+		self.tracks.get(&track_index).into_iter().chain([&self.global])
+	}
+
+	fn apply(&self, track_index:usize, target:VelocityTarget, value:u7, minimum:u8) -> u7 {
+		self.for_track(track_index)
+			.filter(|velocity| velocity.target == target)
+			.fold(value, |value, velocity| velocity.apply(value, minimum))
+	}
+
+	/// Apply the velocity changes to a [`MidiEvent`] from the given track.
+	pub fn apply_to(&self, track_index:usize, midi_event:MidiEvent) -> MidiEvent {
+		let message = match midi_event.message {
+			// (a note on with zero velocity is a note off, so notes are kept at a velocity of at least 1)
+			MidiMessage::NoteOn { key, vel } if u8::from(vel) > 0 => MidiMessage::NoteOn { key, vel: self.apply(track_index, VelocityTarget::Notes, vel, 1) },
+			MidiMessage::Controller { controller, value } => match u8::from(controller) {
+				VOLUME_CONTROLLER => MidiMessage::Controller { controller, value: self.apply(track_index, VelocityTarget::Volume, value, 0) },
+				EXPRESSION_CONTROLLER => MidiMessage::Controller { controller, value: self.apply(track_index, VelocityTarget::Expression, value, 0) },
+				_ => midi_event.message,
+			},
+			message => message,
+		};
+
+		MidiEvent { channel: midi_event.channel, message }
+	}
+
+	/// Whether changing from these settings to the new ones alters the volume or expression of the given track.
+	fn changes_volumes_of(&self, new_settings:&VelocitySettings, track_index:usize) -> bool {
+		let volume_changes = |settings:&VelocitySettings| -> Vec<Velocity> {
+			settings.for_track(track_index).filter(|velocity| velocity.target != VelocityTarget::Notes).copied().collect()
+		};
+		volume_changes(self) != volume_changes(new_settings)
+	}
+}
+
+/// A record of the last volume and expression values the score has sent each track's channels, so that they can be
+/// sent again when the velocity settings that change them do.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChannelVolumes {
+	// (track index, channel) > (volume, expression)
+	volumes: BTreeMap<(usize, u4), (Option<u7>, Option<u7>)>,
+}
+
+impl ChannelVolumes {
+	/// Update the record with a [`MidiEvent`] from the given track, before any velocity changes are applied.
+	pub fn register(&mut self, track_index:usize, midi_event:&MidiEvent) {
+		match midi_event.message {
+			MidiMessage::Controller { controller, value } if u8::from(controller) == VOLUME_CONTROLLER => {
+				self.volumes.entry((track_index, midi_event.channel)).or_default().0 = Some(value);
+			},
+			MidiMessage::Controller { controller, value } if u8::from(controller) == EXPRESSION_CONTROLLER => {
+				self.volumes.entry((track_index, midi_event.channel)).or_default().1 = Some(value);
+			},
+			MidiMessage::NoteOn { .. } => {
+				self.volumes.entry((track_index, midi_event.channel)).or_default();
+			},
+			_ => {}
+		}
+	}
+
+	/// Produce the volume and expression messages (before any velocity changes are applied) which need to be sent
+	/// again to bring the channels in the record in line with the new velocity settings, where they differ from the
+	/// old ones.
+	pub fn refresh(&self, old_settings:&VelocitySettings, new_settings:&VelocitySettings) -> Vec<(usize, MidiEvent)> {
+		self.volumes
+			.iter()
+			.filter(|((track_index, _), _)| old_settings.changes_volumes_of(new_settings, *track_index))
+			.flat_map(|((track_index, channel), (volume, expression))| {
+				[
+					(VOLUME_CONTROLLER, volume.unwrap_or(u7::new(DEFAULT_VOLUME))),
+					(EXPRESSION_CONTROLLER, expression.unwrap_or(u7::new(DEFAULT_EXPRESSION))),
+				]
+					.map(|(controller, value)| (*track_index, MidiEvent { channel: *channel, message: MidiMessage::Controller { controller: u7::new(controller), value } }))
+			})
+			.collect()
+	}
+}
@@ -16,14 +16,37 @@ pub enum Error {
 	Communication(SendError<ToEngine>),
 	/// An [`EngineError`].
 	Engine(EngineError),
+	/// The selected bar, beat and tick position does not exist in the midi score (or the score uses SMPTE timecode).
+	InvalidBbt,
+	/// The selected position in the processor pipeline is beyond its end.
+	InvalidProcessorIndex,
+	/// The selected loop region ends before it begins, or ends beyond the end of the midi score.
+	InvalidLoopRegion,
 	/// Returned when one attempts to set the playback speed to a negative number.
 	NegativeSpeed,
 	/// The engine thread is missing.
 	NoEngine,
-	/// The midi score does not contain any tempo messages.
+	/// The playlist does not contain the requested item.
+	NoSuchItem,
+	/// The midi score does not contain the requested marker.
+	NoSuchMarker,
+	/// The processor pipeline does not contain the requested processor.
+	NoSuchProcessor,
+	/// The midi score does not contain the requested track.
+	NoSuchTrack,
+	/// The midi score does not contain any tempo messages (or is an SMPTE timecode score, which has no tempo).
 	NoTempo,
 	/// A [`ScoreError`].
 	Score(ScoreError),
+	/// A [`MidirError`](crate::MidirError).
+	#[cfg(feature = "midir")]
+	Midir(crate::MidirError),
+	/// A [`SynthError`](crate::SynthError).
+	#[cfg(feature = "synth")]
+	Synth(crate::SynthError),
+	/// A [`Processor`](crate::Processor) in the pipeline cannot be duplicated (see
+	/// [`Processor::try_clone`](crate::Processor::try_clone)), so the performance cannot be rendered.
+	UncloneableProcessor,
 	/// An error returned by the [`std::thread::JoinHandle::join`] method of the thread holding the engine.
 	Thread(Box<dyn Any + Send>),
 }
@@ -32,4 +55,17 @@ impl From<ScoreError> for Error {
 	fn from(score_error:ScoreError) -> Error {
 		Error::Score(score_error)
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "midir")]
+impl From<crate::MidirError> for Error {
+	fn from(midir_error:crate::MidirError) -> Error {
+		Error::Midir(midir_error)
+	}
+}
+#[cfg(feature = "synth")]
+impl From<crate::SynthError> for Error {
+	fn from(synth_error:crate::SynthError) -> Error {
+		Error::Synth(synth_error)
+	}
+}
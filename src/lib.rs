@@ -3,28 +3,68 @@
 #![allow(clippy::must_use_candidate)]
 #![allow(clippy::needless_pass_by_value)]
 
-use std::{thread::JoinHandle, time::Duration};
+use std::{collections::BTreeMap, sync::{Arc, Mutex}, thread::JoinHandle, time::Duration};
 
-use midly::Smf;
+use midly::{num::u4, Smf};
 
 mod sleep;
+mod clock;
+use clock::{Clock, SystemClock};
 mod timer;
 use timer::Timer;
 mod score;
-use score::{Event, Score};
+use score::Score;
 pub use score::Error as ScoreError;
-pub use score::MidiEvent;
+pub use score::{Event, EventKinds, MetaEvent, MidiEvent};
+pub use score::{Line, Lyrics, Syllable};
+pub use score::{Marker, MarkerKind};
+pub use score::Bbt;
+pub use score::Chase;
 mod messages;
 use messages::{ToConsole, ToEngine};
 mod engine;
 use engine::engine;
 pub use engine::Error as EngineError;
+pub use engine::NoteRelease;
+pub use engine::LoopRegion;
+pub use engine::Render;
+pub use engine::OutOfRangeNotes;
+pub use engine::{Velocity, VelocityTarget};
+use engine::{ChannelMap, Mute, Transpose, VelocitySettings};
+mod load_position;
+pub use load_position::LoadPosition;
+mod notification;
+pub use notification::Notification;
+mod timestamp;
+pub use timestamp::{Timestamp, TimestampedEvent};
+mod sink;
+pub use sink::{Sink, SinkError};
+mod processor;
+pub use processor::{Processor, ProcessorId};
+pub use processor::{FilterProcessor, RemapProcessor, TransposeProcessor, VelocityProcessor};
+use sink::ChannelSink;
+#[cfg(feature = "midir")]
+mod midir_sink;
+#[cfg(feature = "midir")]
+pub use midir_sink::{MidirSink, Error as MidirError};
+#[cfg(feature = "synth")]
+mod synth;
+#[cfg(feature = "synth")]
+pub use synth::{Synth, Error as SynthError};
+#[cfg(feature = "soundfont")]
+pub use synth::SoundFont;
 mod error;
 pub use error::Error;
 
+#[cfg(test)]
+mod tests;
+
 /// A struct for playing MIDI scores.
 pub struct Performer {
-	score: Score,
+	playlist: Vec<Arc<Score>>,
+	current_item: usize,
+	shuffle: bool,
+	repeat_all: bool,
 
 	engine_thread_handle: Option<JoinHandle<Result<(), EngineError>>>,
 	channel_to_engine: crossbeam_channel::Sender<ToEngine>,
@@ -33,35 +73,80 @@ pub struct Performer {
 	is_playing: bool,
 	position: usize,
 	speed: f32,
-	looping: bool
+	looping: bool,
+	loop_region: Option<LoopRegion>,
+	note_release: NoteRelease,
+	chase: Chase,
+	lookahead: Duration,
+	track_mute: Mute<usize>,
+	channel_mute: Mute<u4>,
+	channel_map: ChannelMap,
+	transpose: Transpose,
+	velocity: VelocitySettings,
+	processors: Vec<ProcessorId>,
+	// (copies of the processors as they were added, for rendering, behind a mutex as processors need not be Sync)
+	processor_copies: Mutex<BTreeMap<ProcessorId, Option<Box<dyn Processor>>>>,
+	next_processor_id: usize,
+
+	notifications: Vec<Notification>
 }
 
 impl Performer {
-	/// Create an instance of a [Performer] using the provided [Smf] data
+	/// Create an instance of a [Performer] using the provided [Smf] data.
+	/// Events are collected through [`Performer::poll`].
 	///
 	/// # Errors
-	/// Will return an error if the timing value of the [Smf] data's header is not [`midly::Timing::Metrical`].
+	/// Will return an error if the timing value of the [Smf] data's header describes zero ticks per beat (or per frame),
+	/// or if a [`midly::Timing::Metrical`] score does not contain any tempo messages.
 	pub fn try_new(standard_midi_file:Smf) -> Result<Performer, Error> {
-		let score = Score::new(&standard_midi_file)?;
+		let (channel_to_console, channel_from_engine) = crossbeam_channel::unbounded::<ToConsole>();
+		let sink = ChannelSink::new(channel_to_console.clone());
+		Performer::try_new_with_channels(standard_midi_file, sink, channel_to_console, channel_from_engine, Box::new(SystemClock))
+	}
 
-		let (channel_to_engine, channel_from_console) = crossbeam_channel::unbounded::<ToEngine>();
+	/// Create an instance of a [Performer] using the provided [Smf] data, which will deliver its events to the provided [Sink].
+	///
+	/// The sink is called directly from the engine thread, so [`Performer::poll`] will not return any events, though
+	/// it should still be called occasionally to keep the performer's playback state up to date.
+	///
+	/// # Errors
+	/// Will return an error if the timing value of the [Smf] data's header describes zero ticks per beat (or per frame),
+	/// or if a [`midly::Timing::Metrical`] score does not contain any tempo messages.
+	pub fn try_new_with_sink(standard_midi_file:Smf, sink:impl Sink + 'static) -> Result<Performer, Error> {
 		let (channel_to_console, channel_from_engine) = crossbeam_channel::unbounded::<ToConsole>();
+		Performer::try_new_with_channels(standard_midi_file, sink, channel_to_console, channel_from_engine, Box::new(SystemClock))
+	}
+
+	fn try_new_with_channels(
+		standard_midi_file: Smf,
+		sink: impl Sink + 'static,
+		channel_to_console: crossbeam_channel::Sender<ToConsole>,
+		channel_from_engine: crossbeam_channel::Receiver<ToConsole>,
+		clock: Box<dyn Clock>
+	) -> Result<Performer, Error> {
+		let score = Arc::new(Score::new(&standard_midi_file)?);
 
-		let score_clone = score.clone();
+		let (channel_to_engine, channel_from_console) = crossbeam_channel::unbounded::<ToEngine>();
+
+		let score_clone = Arc::clone(&score);
 		let engine_thread_handle = Some(
 			std::thread::spawn(move || {
 				engine(
 					&channel_from_console,
-					&channel_to_console,
-					&score_clone,
-					standard_midi_file.header.timing
+					channel_to_console,
+					Box::new(sink),
+					clock,
+					score_clone
 				)
 			})
 		);
 
 		Ok(
 			Performer {
-				score,
+				playlist: vec![score],
+				current_item: 0,
+				shuffle: false,
+				repeat_all: false,
 
 				engine_thread_handle,
 				channel_to_engine,
@@ -70,7 +155,21 @@ impl Performer {
 				is_playing: false,
 				position: 0,
 				speed: 1.0,
-				looping: false
+				looping: false,
+				loop_region: None,
+				note_release: NoteRelease::default(),
+				chase: Chase::default(),
+				lookahead: Duration::ZERO,
+				track_mute: Mute::default(),
+				channel_mute: Mute::default(),
+				channel_map: ChannelMap::default(),
+				transpose: Transpose::default(),
+				velocity: VelocitySettings::default(),
+				processors: vec![],
+				processor_copies: Mutex::default(),
+				next_processor_id: 0,
+
+				notifications: vec![]
 			}
 		)
 	
@@ -79,15 +178,31 @@ impl Performer {
 	/// Create an instance of a [Performer] using the provided [Smf] data.
 	///
 	/// # Panics
-	/// Will panic if the timing value of the [Smf] data's header is not [`midly::Timing::Metrical`].
+	/// Will panic if the timing value of the [Smf] data's header describes zero ticks per beat (or per frame),
+	/// or if a [`midly::Timing::Metrical`] score does not contain any tempo messages.
 	pub fn new(standard_midi_file:Smf) -> Performer {
 		Performer::try_new(standard_midi_file).ok().unwrap()
 	}
+
+	/// Create an instance of a [Performer] using the provided [Smf] data, which will deliver its events to the provided [Sink].
+	///
+	/// # Panics
+	/// Will panic if the timing value of the [Smf] data's header describes zero ticks per beat (or per frame),
+	/// or if a [`midly::Timing::Metrical`] score does not contain any tempo messages.
+	pub fn new_with_sink(standard_midi_file:Smf, sink:impl Sink + 'static) -> Performer {
+		Performer::try_new_with_sink(standard_midi_file, sink).ok().unwrap()
+	}
+}
+
+impl Performer {
+	fn score(&self) -> &Score {
+		&self.playlist[self.current_item]
+	}
 }
 
 impl Performer {
 	pub fn get_track_count(&self) -> usize {
-		self.score.get_track_count()
+		self.score().get_track_count()
 	}
 
 	pub fn is_playing(&self) -> bool {
@@ -99,42 +214,166 @@ impl Performer {
 	pub fn is_looping(&self) -> bool {
 		self.looping
 	}
+	pub fn get_loop_region(&self) -> Option<LoopRegion> {
+		self.loop_region
+	}
+	pub fn get_note_release(&self) -> NoteRelease {
+		self.note_release
+	}
+	pub fn get_chase(&self) -> Chase {
+		self.chase
+	}
+	pub fn get_lookahead(&self) -> Duration {
+		self.lookahead
+	}
+	pub fn is_track_muted(&self, track:usize) -> bool {
+		self.track_mute.is_muted(track)
+	}
+	pub fn is_track_soloed(&self, track:usize) -> bool {
+		self.track_mute.is_soloed(track)
+	}
+	/// Whether the notes of a track can be heard: it isn't muted and, if any tracks are soloed, it is one of them.
+	pub fn is_track_audible(&self, track:usize) -> bool {
+		self.track_mute.is_audible(track)
+	}
+	pub fn is_channel_muted(&self, channel:u4) -> bool {
+		self.channel_mute.is_muted(channel)
+	}
+	pub fn is_channel_soloed(&self, channel:u4) -> bool {
+		self.channel_mute.is_soloed(channel)
+	}
+	/// Whether the notes of a channel can be heard: it isn't muted and, if any channels are soloed, it is one of them.
+	pub fn is_channel_audible(&self, channel:u4) -> bool {
+		self.channel_mute.is_audible(channel)
+	}
+	/// Get the channel that the events of the given channel of the score are sent to.
+	pub fn get_channel_destination(&self, channel:u4) -> u4 {
+		self.channel_map.get(channel)
+	}
+	pub fn get_transpose(&self) -> i8 {
+		self.transpose.semitones
+	}
+	pub fn get_track_transpose(&self, track:usize) -> i8 {
+		self.transpose.tracks.get(&track).copied().unwrap_or(0)
+	}
+	pub fn get_channel_transpose(&self, channel:u4) -> i8 {
+		self.transpose.channels.get(&channel).copied().unwrap_or(0)
+	}
+	pub fn is_transposing_drums(&self) -> bool {
+		self.transpose.drums
+	}
+	pub fn get_out_of_range_notes(&self) -> OutOfRangeNotes {
+		self.transpose.out_of_range_notes
+	}
+	pub fn get_velocity(&self) -> Velocity {
+		self.velocity.global
+	}
+	pub fn get_track_velocity(&self, track:usize) -> Velocity {
+		self.velocity.tracks.get(&track).copied().unwrap_or_default()
+	}
+	/// Get the [Processor]s in the pipeline, in the order events pass through them.
+	pub fn get_processors(&self) -> &[ProcessorId] {
+		&self.processors
+	}
+
+	/// Get the number of items (scores) in the playlist.
+	pub fn get_item_count(&self) -> usize {
+		self.playlist.len()
+	}
+	/// Get the index of the playlist item currently being played, in the order the items were queued.
+	pub fn get_current_item(&self) -> usize {
+		self.current_item
+	}
+	pub fn is_shuffling(&self) -> bool {
+		self.shuffle
+	}
+	pub fn is_repeating_all(&self) -> bool {
+		self.repeat_all
+	}
 
 	pub fn get_length_in_ticks(&self) -> usize {
-		self.score.len()
+		self.score().len()
 	}
 	pub fn get_position_in_ticks(&self) -> usize {
 		self.position
 	}
 	pub fn get_length_in_duration(&self) -> Duration {
-		self.score.calculate_duration(self.speed)
+		self.score().calculate_duration(self.speed)
 	}
 	pub fn get_position_in_duration(&self) -> Duration {
-		self.score.calculate_duration_until(self.speed, self.position)
+		self.score().calculate_duration_until(self.speed, self.position)
+	}
+	/// Convert a time (when playing from the beginning at the current speed) to the tick position reached,
+	/// accounting for every tempo change along the way.
+	pub fn ticks_at_duration(&self, duration:Duration) -> usize {
+		self.score().calculate_ticks_at_duration(self.speed, duration)
+	}
+	/// Get the current position in bars, beats and ticks. Returns `None` for SMPTE timecode scores.
+	pub fn get_position_in_bbt(&self) -> Option<Bbt> {
+		self.score().ticks_to_bbt(self.position)
 	}
 	
 	/// Get the number of microseconds per beat at the current position
 	///
+	/// SMPTE timecode scores measure time in frames rather than beats (ignoring any tempo messages), so have no tempo.
+	///
 	/// # Errors
-	/// Will return an [`Error::NoTempo`] if the midi score does not contain any tempo messages.
+	/// Will return an [`Error::NoTempo`] if the midi score does not contain any tempo messages, or is an SMPTE
+	/// timecode score.
 	pub fn get_current_microseconds_per_beat(&self) -> Result<usize, Error> {
-		let position = if self.position >= self.score.len() {
-			self.score.len() - 1
+		let position = if self.position >= self.score().len() {
+			self.score().len() - 1
 		} else {
 			self.position
 		};
 
-		if let Some(microseconds_per_beat) = self.score.get_microseconds_per_beat_at(position) {
+		if let Some(microseconds_per_beat) = self.score().get_microseconds_per_beat_at(position) {
 			Ok(u32::from(microseconds_per_beat) as usize)
 		} else {
 			Err(Error::NoTempo)
 		}
 	}
 
+	/// Convert a tick position to a [Bbt] position. Returns `None` for SMPTE timecode scores.
+	pub fn ticks_to_bbt(&self, ticks:usize) -> Option<Bbt> {
+		self.score().ticks_to_bbt(ticks)
+	}
+	/// Convert a [Bbt] position to a tick position. Returns `None` for SMPTE timecode scores, or if the beat or tick
+	/// lies outside of its bar.
+	pub fn bbt_to_ticks(&self, bbt:Bbt) -> Option<usize> {
+		self.score().bbt_to_ticks(bbt)
+	}
+	/// Convert a [Bbt] position to the time it would be reached when playing from the beginning at the current speed.
+	pub fn bbt_to_duration(&self, bbt:Bbt) -> Option<Duration> {
+		self.score().bbt_to_ticks(bbt).map(|ticks| self.score().calculate_duration_until(self.speed, ticks))
+	}
+	/// Convert a time (when playing from the beginning at the current speed) to the [Bbt] position reached.
+	pub fn duration_to_bbt(&self, duration:Duration) -> Option<Bbt> {
+		self.score().ticks_to_bbt(self.score().calculate_ticks_at_duration(self.speed, duration))
+	}
+
+	/// Get the lyrics of the midi score.
+	pub fn get_lyrics(&self) -> &Lyrics {
+		self.score().get_lyrics()
+	}
+
+	/// Get the index of the current syllable of the score's lyrics (the latest syllable at or before the current position).
+	pub fn get_current_syllable(&self) -> Option<usize> {
+		self.score().get_lyrics().get_syllable_index_at_or_before(self.position)
+	}
+
+	/// Get the markers and cue points of the midi score, ordered by position.
+	pub fn get_markers(&self) -> &[Marker] {
+		self.score().get_markers()
+	}
+
 	/// Get the number of beats per minute at the current position
 	///
+	/// SMPTE timecode scores measure time in frames rather than beats (ignoring any tempo messages), so have no tempo.
+	///
 	/// # Errors
-	/// Will return an [`Error::NoTempo`] if the midi score does not contain any tempo messages.
+	/// Will return an [`Error::NoTempo`] if the midi score does not contain any tempo messages, or is an SMPTE
+	/// timecode score.
 	pub fn get_current_bpm(&self) -> Result<f32, Error> {
 		Ok(1.0 / ((self.get_current_microseconds_per_beat()? as f32 / 1_000_000.0) / 60.0))
 	}
@@ -156,6 +395,8 @@ impl Performer {
 
 	/// Instruct the engine to pause playing the midi score.
 	///
+	/// Any notes left sounding are released as described by the current [`NoteRelease`] setting.
+	///
 	/// # Errors
 	/// Will return an [`Error::Communication`] if there is a communication issue with the engine.
 	pub fn pause(&mut self) -> Result<(), Error> {
@@ -169,6 +410,8 @@ impl Performer {
 
 	/// Instruct the engine to stop playing the midi score, returning the playhead to position 0.
 	///
+	/// Any notes left sounding are released as described by the current [`NoteRelease`] setting.
+	///
 	/// # Errors
 	/// Will return an [`Error::Communication`] if there is a communication issue with the engine.
 	pub fn stop(&mut self) -> Result<(), Error> {
@@ -182,10 +425,14 @@ impl Performer {
 
 	/// Instruct the engine to jump to a certain position in the midi score.
 	///
+	/// Any notes left sounding are released as described by the current [`NoteRelease`] setting, and the
+	/// channel state at the new position is reconstructed as described by the current [`Chase`] setting.
+	///
 	/// # Errors
-	/// Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	/// - Will return an [`Error::BeyondScoreLength`] if the position is beyond the end of the score.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
 	pub fn jump_to(&mut self, position:usize) -> Result<(), Error> {
-		if position >= self.score.len() {
+		if position >= self.score().len() {
 			return Err(Error::BeyondScoreLength);
 		}
 
@@ -198,6 +445,67 @@ impl Performer {
 		}
 	}
 
+	/// Instruct the engine to jump to the position reached after playing for the given duration from the beginning of
+	/// the midi score at the current speed (ie. the position at which [`Performer::get_position_in_duration`] would
+	/// return this duration).
+	///
+	/// # Errors
+	/// - Will return an [`Error::BeyondScoreLength`] if the position is beyond the end of the score.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn jump_to_duration(&mut self, duration:Duration) -> Result<(), Error> {
+		self.jump_to(self.ticks_at_duration(duration))
+	}
+
+	/// Instruct the engine to jump to a certain bar, beat and tick position in the midi score.
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidBbt`] if the position does not exist (or the score uses SMPTE timecode).
+	/// - Will return an [`Error::BeyondScoreLength`] if the position is beyond the end of the score.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn jump_to_bbt(&mut self, bbt:Bbt) -> Result<(), Error> {
+		let Some(position) = self.score().bbt_to_ticks(bbt) else {
+			return Err(Error::InvalidBbt);
+		};
+
+		self.jump_to(position)
+	}
+
+	/// Instruct the engine to jump to the beginning of a certain bar (counting from 1) of the midi score.
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidBbt`] if the bar does not exist (or the score uses SMPTE timecode).
+	/// - Will return an [`Error::BeyondScoreLength`] if the bar begins beyond the end of the score.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn jump_to_bar(&mut self, bar:usize) -> Result<(), Error> {
+		self.jump_to_bbt(Bbt::new(bar, 1, 0))
+	}
+
+	/// Instruct the engine to jump to the position of the marker with the given index (into [`Performer::get_markers`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchMarker`] if there is no marker with the given index.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn jump_to_marker(&mut self, marker_index:usize) -> Result<(), Error> {
+		let Some(marker) = self.score().get_markers().get(marker_index) else {
+			return Err(Error::NoSuchMarker);
+		};
+
+		self.jump_to(marker.tick)
+	}
+
+	/// Instruct the engine to jump to the position of the first marker with the given name.
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchMarker`] if there is no marker with the given name.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn jump_to_marker_named(&mut self, name:&str) -> Result<(), Error> {
+		let Some(marker_index) = self.score().get_markers().iter().position(|marker| marker.name == name) else {
+			return Err(Error::NoSuchMarker);
+		};
+
+		self.jump_to_marker(marker_index)
+	}
+
 	/// Set the playback speed (as a multiple of the tempo defined in the midi score)
 	///
 	/// # Errors
@@ -219,6 +527,8 @@ impl Performer {
 
 	/// Instruct the engine to return to the beginning of the midi score when it reaches the end.
 	///
+	/// Any notes left sounding are released as described by the current [`NoteRelease`] setting.
+	///
 	/// # Errors
 	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
 	pub fn set_looping(&mut self, looping:bool) -> Result<(), Error> {
@@ -230,6 +540,586 @@ impl Performer {
 			Ok(())
 		}
 	}
+
+	/// Instruct the engine to repeatedly play a region of the midi score, returning from the region's end to its start
+	/// (restoring the tempo and, as described by the current [`Chase`] setting, the channel state) the given number of
+	/// times, or indefinitely if `repeats` is `None`. A [`Notification::LoopWrapped`] is sent each time playback returns
+	/// to the start. Only one loop region may be set at a time, and setting one resets its count of repeats.
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidLoopRegion`] if the region ends before it begins, or ends beyond the end of the score.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_loop_region(&mut self, start:usize, end:usize, repeats:Option<usize>) -> Result<(), Error> {
+		if start >= end || end > self.score().len() {
+			return Err(Error::InvalidLoopRegion);
+		}
+
+		self.send_loop_region(Some(LoopRegion::new(start, end, repeats)))
+	}
+
+	/// Instruct the engine to repeatedly play a region of the midi score, given in bars, beats and ticks.
+	/// See [`Performer::set_loop_region`].
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidBbt`] if either position does not exist (or the score uses SMPTE timecode).
+	/// - Will return an [`Error::InvalidLoopRegion`] if the region ends before it begins, or ends beyond the end of the score.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_loop_region_bbt(&mut self, start:Bbt, end:Bbt, repeats:Option<usize>) -> Result<(), Error> {
+		let (Some(start), Some(end)) = (self.score().bbt_to_ticks(start), self.score().bbt_to_ticks(end)) else {
+			return Err(Error::InvalidBbt);
+		};
+
+		self.set_loop_region(start, end, repeats)
+	}
+
+	/// Instruct the engine to repeatedly play a region of the midi score, running from one marker to another
+	/// (given as indices into [`Performer::get_markers`]), or to the end of the score if `end_marker_index` is `None`.
+	/// See [`Performer::set_loop_region`].
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchMarker`] if either marker does not exist.
+	/// - Will return an [`Error::InvalidLoopRegion`] if the region ends before it begins.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_loop_region_markers(&mut self, start_marker_index:usize, end_marker_index:Option<usize>, repeats:Option<usize>) -> Result<(), Error> {
+		let markers = self.score().get_markers();
+		let Some(start) = markers.get(start_marker_index).map(|marker| marker.tick) else {
+			return Err(Error::NoSuchMarker);
+		};
+		let end = match end_marker_index {
+			Some(end_marker_index) => {
+				let Some(end) = markers.get(end_marker_index).map(|marker| marker.tick) else {
+					return Err(Error::NoSuchMarker);
+				};
+				end
+			},
+			None => self.score().len()
+		};
+
+		self.set_loop_region(start, end, repeats)
+	}
+
+	/// Instruct the engine to stop repeating the loop region (if any), letting playback continue past its end.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn clear_loop_region(&mut self) -> Result<(), Error> {
+		self.send_loop_region(None)
+	}
+
+	fn send_loop_region(&mut self, loop_region:Option<LoopRegion>) -> Result<(), Error> {
+		self.loop_region = loop_region;
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetLoopRegion(loop_region)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Select the messages the engine sends to silence any sounding notes when playback is interrupted.
+	/// These messages are sent to the performer's sink alongside the rest of the score's events.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_note_release(&mut self, note_release:NoteRelease) -> Result<(), Error> {
+		self.note_release = note_release;
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetNoteRelease(note_release)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Have the engine send events ahead of time by the given duration, so that they can be scheduled (into an audio
+	/// buffer, for example) for the moment they are meant to be heard, as given by the [Timestamp]s returned by
+	/// [`Performer::poll_timestamped`] or passed to [`Sink::receive_timestamped`]. By default, there is no lookahead.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_lookahead(&mut self, lookahead:Duration) -> Result<(), Error> {
+		self.lookahead = lookahead;
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetLookahead(lookahead)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Select the kinds of channel state (program changes, controller values, etc.) the engine reconstructs and sends
+	/// when jumping into the middle of the midi score or looping back to its start. By default, nothing is chased.
+	/// These messages are sent to the performer's sink alongside the rest of the score's events.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_chase(&mut self, chase:Chase) -> Result<(), Error> {
+		self.chase = chase;
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetChase(chase)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Mute (or unmute) a track. The engine stops sending the track's notes straight away, and releases any that are
+	/// sounding as described by the current [`NoteRelease`] setting; its other events (controllers, program changes,
+	/// etc.) are still sent, so that it sounds right when unmuted.
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchTrack`] if the midi score does not contain the track.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn mute_track(&mut self, track:usize, muted:bool) -> Result<(), Error> {
+		if track >= self.get_track_count() {
+			return Err(Error::NoSuchTrack);
+		}
+
+		self.track_mute.set_muted(track, muted);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::MuteTrack(track, muted)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Solo (or unsolo) a track. While any tracks are soloed, the notes of every other track are held back as though
+	/// they were muted (see [`Performer::mute_track`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchTrack`] if the midi score does not contain the track.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn solo_track(&mut self, track:usize, soloed:bool) -> Result<(), Error> {
+		if track >= self.get_track_count() {
+			return Err(Error::NoSuchTrack);
+		}
+
+		self.track_mute.set_soloed(track, soloed);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SoloTrack(track, soloed)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Shift the notes of the whole score up (or down) by the given number of semitones, taking effect straight away.
+	///
+	/// Notes already sounding are ended at the key they began at, however the transposition changes in the meantime.
+	/// The drum channel (channel 10) is left alone unless [`Performer::set_transpose_drums`] says otherwise, and notes
+	/// shifted out of the range of MIDI keys are treated as [`Performer::set_out_of_range_notes`] describes.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_transpose(&mut self, semitones:i8) -> Result<(), Error> {
+		self.transpose.semitones = semitones;
+		self.send_transpose()
+	}
+
+	/// Shift the notes of one track by the given number of semitones, on top of the transposition of the whole score
+	/// (see [`Performer::set_transpose`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchTrack`] if the midi score does not contain the track.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_track_transpose(&mut self, track:usize, semitones:i8) -> Result<(), Error> {
+		if track >= self.get_track_count() {
+			return Err(Error::NoSuchTrack);
+		}
+
+		if semitones == 0 {
+			self.transpose.tracks.remove(&track);
+		} else {
+			self.transpose.tracks.insert(track, semitones);
+		}
+		self.send_transpose()
+	}
+
+	/// Shift the notes of one channel of the score (before any remapping) by the given number of semitones, on top of
+	/// the transposition of the whole score and of the track they're in (see [`Performer::set_transpose`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_channel_transpose(&mut self, channel:u4, semitones:i8) -> Result<(), Error> {
+		if semitones == 0 {
+			self.transpose.channels.remove(&channel);
+		} else {
+			self.transpose.channels.insert(channel, semitones);
+		}
+		self.send_transpose()
+	}
+
+	/// Select whether the drum channel (channel 10), whose keys select instruments rather than pitches, is
+	/// transposed too. By default, it is not.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_transpose_drums(&mut self, transpose_drums:bool) -> Result<(), Error> {
+		self.transpose.drums = transpose_drums;
+		self.send_transpose()
+	}
+
+	/// Select what happens to notes transposed beyond the range of MIDI keys. By default, they are dropped.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_out_of_range_notes(&mut self, out_of_range_notes:OutOfRangeNotes) -> Result<(), Error> {
+		self.transpose.out_of_range_notes = out_of_range_notes;
+		self.send_transpose()
+	}
+
+	fn send_transpose(&self) -> Result<(), Error> {
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetTranspose(self.transpose.clone())) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Change the velocities of every note in the score (or, depending on the [`VelocityTarget`], the volume of every
+	/// channel) as they are played, taking effect straight away. Use [`Velocity::default`] to undo this.
+	///
+	/// When volume or expression messages are being changed, the current values are sent again to every channel
+	/// affected, so that the change can be heard without waiting for the score to send new ones.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_velocity(&mut self, velocity:Velocity) -> Result<(), Error> {
+		self.velocity.global = velocity;
+		self.send_velocity()
+	}
+
+	/// Change the velocities of the notes of one track (or its volume), before the change made to the whole score (see
+	/// [`Performer::set_velocity`]) is applied.
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchTrack`] if the midi score does not contain the track.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_track_velocity(&mut self, track:usize, velocity:Velocity) -> Result<(), Error> {
+		if track >= self.get_track_count() {
+			return Err(Error::NoSuchTrack);
+		}
+
+		if velocity.is_identity() {
+			self.velocity.tracks.remove(&track);
+		} else {
+			self.velocity.tracks.insert(track, velocity);
+		}
+		self.send_velocity()
+	}
+
+	fn send_velocity(&self) -> Result<(), Error> {
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetVelocity(self.velocity.clone())) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Mute (or unmute) a channel of the score, for scores (such as Format 0 files) which hold several parts in one
+	/// track. As with [`Performer::mute_track`], only the channel's notes are held back, and any that are sounding are
+	/// released.
+	///
+	/// (Channels are muted by their number in the score, before any remapping.)
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn mute_channel(&mut self, channel:u4, muted:bool) -> Result<(), Error> {
+		self.channel_mute.set_muted(channel, muted);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::MuteChannel(channel, muted)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Solo (or unsolo) a channel of the score. While any channels are soloed, the notes of every other channel are
+	/// held back as though they were muted (see [`Performer::mute_channel`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn solo_channel(&mut self, channel:u4, soloed:bool) -> Result<(), Error> {
+		self.channel_mute.set_soloed(channel, soloed);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SoloChannel(channel, soloed)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Send every event of a channel of the score (notes, controllers, program changes, etc.) to another channel
+	/// instead; for example, to move the drums from channel 10 to channel 16. Remap a channel to itself to undo this.
+	///
+	/// Any notes sounding on the channel the events were sent to before are released, as described by the current
+	/// [`NoteRelease`] setting.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn remap_channel(&mut self, channel:u4, destination:u4) -> Result<(), Error> {
+		self.channel_map.set(channel, destination);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::RemapChannel(channel, destination)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl Performer {
+	/// Add a [Processor] to the end of the pipeline every event passes through in the engine thread, returning the
+	/// [`ProcessorId`] by which it can later be moved or removed.
+	///
+	/// Any notes sounding are released (as described by the current [`NoteRelease`] setting) whenever the pipeline
+	/// changes, so that none are left hanging by a processor that has moved them. A [`Performer::render`] runs copies of
+	/// the processors (see [`Processor::try_clone`]), as they were when added.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn add_processor(&mut self, processor:impl Processor + 'static) -> Result<ProcessorId, Error> {
+		self.insert_processor(self.processors.len(), processor)
+	}
+
+	/// Insert a [Processor] into the pipeline at the given index (see [`Performer::add_processor`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidProcessorIndex`] if the index is beyond the end of the pipeline.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn insert_processor(&mut self, index:usize, processor:impl Processor + 'static) -> Result<ProcessorId, Error> {
+		if index > self.processors.len() {
+			return Err(Error::InvalidProcessorIndex);
+		}
+
+		let id = ProcessorId(self.next_processor_id);
+		self.next_processor_id += 1;
+		self.processors.insert(index, id);
+		self.processor_copies.get_mut().expect("the processor copies are only locked while being cloned").insert(id, processor.try_clone());
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::InsertProcessor(index, id, Box::new(processor))) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(id)
+		}
+	}
+
+	/// Remove a [Processor] from the pipeline.
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchProcessor`] if the processor is not in the pipeline.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn remove_processor(&mut self, id:ProcessorId) -> Result<(), Error> {
+		let Some(index) = self.processors.iter().position(|processor_id| *processor_id == id) else {
+			return Err(Error::NoSuchProcessor);
+		};
+		self.processors.remove(index);
+		self.processor_copies.get_mut().expect("the processor copies are only locked while being cloned").remove(&id);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::RemoveProcessor(id)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Move a [Processor] to the given index of the pipeline.
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchProcessor`] if the processor is not in the pipeline.
+	/// - Will return an [`Error::InvalidProcessorIndex`] if the index is beyond the end of the pipeline.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn move_processor(&mut self, id:ProcessorId, index:usize) -> Result<(), Error> {
+		let Some(current_index) = self.processors.iter().position(|processor_id| *processor_id == id) else {
+			return Err(Error::NoSuchProcessor);
+		};
+		if index >= self.processors.len() {
+			return Err(Error::InvalidProcessorIndex);
+		}
+
+		self.processors.remove(current_index);
+		self.processors.insert(index, id);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::MoveProcessor(id, index)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl Performer {
+	/// Render the performance, as currently set up, as fast as possible rather than in real time.
+	///
+	/// The returned [Render] iterates over every [Event] (including those sent to silence notes and chase channel
+	/// state) along with its track number and the time, from the start of the render, at which real-time playback
+	/// would send it. Rendering begins from the current item and position, with the current speed, looping, loop
+	/// region, note release, chase, filtering and playlist settings, and copies of the processors in the pipeline; the
+	/// performer itself is unaffected.
+	/// (When shuffling, the order of the items following the current one will differ from that of real-time playback.)
+	///
+	/// # Errors
+	/// - Will return an [`Error::UncloneableProcessor`] if a processor in the pipeline cannot be duplicated.
+	/// - Will return an [`Error::NoSuchProcessor`] if no copy was kept of a processor in the pipeline.
+	/// - Will return an [`Error::Engine`] if the engine could not be prepared. (Errors encountered while rendering are
+	///   given by [`Render::error`].)
+	pub fn render(&self) -> Result<Render, Error> {
+		let processors = {
+			let processor_copies = self.processor_copies.lock().expect("the processor copies are only locked while being cloned");
+			self.processors
+				.iter()
+				.map(|id| match processor_copies.get(id) {
+					Some(Some(processor)) => processor.try_clone().map(|processor| (*id, processor)).ok_or(Error::UncloneableProcessor),
+					Some(None) => Err(Error::UncloneableProcessor),
+					None => Err(Error::NoSuchProcessor),
+				})
+				.collect::<Result<Vec<_>, Error>>()?
+		};
+
+		let mut messages = vec![];
+		if self.current_item != 0 {
+			messages.push(ToEngine::SkipToItem(self.current_item));
+		}
+		messages.extend([
+			ToEngine::SetSpeed(self.speed),
+			ToEngine::SetLooping(self.looping),
+			ToEngine::SetNoteRelease(self.note_release),
+			ToEngine::SetChase(self.chase),
+			ToEngine::SetShuffle(self.shuffle),
+			ToEngine::SetRepeatAll(self.repeat_all),
+		]);
+		messages.push(ToEngine::SetTranspose(self.transpose.clone()));
+		messages.push(ToEngine::SetVelocity(self.velocity.clone()));
+		messages.extend(self.track_mute.get_muted().map(|track| ToEngine::MuteTrack(track, true)));
+		messages.extend(self.track_mute.get_soloed().map(|track| ToEngine::SoloTrack(track, true)));
+		messages.extend(self.channel_mute.get_muted().map(|channel| ToEngine::MuteChannel(channel, true)));
+		messages.extend(self.channel_mute.get_soloed().map(|channel| ToEngine::SoloChannel(channel, true)));
+		messages.extend(self.channel_map.get_remapped().map(|(channel, destination)| ToEngine::RemapChannel(channel, destination)));
+		messages.extend(processors.into_iter().enumerate().map(|(index, (id, processor))| ToEngine::InsertProcessor(index, id, processor)));
+		messages.extend([
+			ToEngine::JumpTo(self.position),
+			ToEngine::SetLoopRegion(self.loop_region),
+			ToEngine::Play,
+		]);
+
+		Render::new(&self.playlist, messages).map_err(Error::Engine)
+	}
+
+	/// Replace the score currently being played with the one described by the provided [Smf] data, without stopping
+	/// the engine, placing the playhead as described by the [`LoadPosition`].
+	///
+	/// Any notes left sounding are released as described by the current [`NoteRelease`] setting, the channel state is
+	/// chased as described by the current [`Chase`] setting, and any loop region is cleared.
+	///
+	/// The engine replaces whichever item it is playing when the score arrives, which may have moved on since the last
+	/// poll; the performer's getters reflect the new score once the engine has reported the replacement (when next
+	/// polled).
+	///
+	/// # Errors
+	/// - Will return an [`Error::Score`] if the timing value of the [Smf] data's header describes zero ticks per beat
+	///   (or per frame), or if a [`midly::Timing::Metrical`] score does not contain any tempo messages.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn load(&mut self, standard_midi_file:Smf, load_position:LoadPosition) -> Result<(), Error> {
+		let score = Arc::new(Score::new(&standard_midi_file)?);
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::Load(score, load_position)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Add the score described by the provided [Smf] data to the end of the playlist, returning its item index.
+	///
+	/// When the engine reaches the end of one item it moves straight on to the next, without a gap, sending a
+	/// [`Notification::ItemStarted`]. The getters and commands of the [Performer] always refer to the item currently
+	/// being played. (When shuffling, the new item is placed at random among the items yet to be played.)
+	///
+	/// # Errors
+	/// - Will return an [`Error::Score`] if the timing value of the [Smf] data's header describes zero ticks per beat
+	///   (or per frame), or if a [`midly::Timing::Metrical`] score does not contain any tempo messages.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn enqueue(&mut self, standard_midi_file:Smf) -> Result<usize, Error> {
+		let score = Arc::new(Score::new(&standard_midi_file)?);
+		self.playlist.push(Arc::clone(&score));
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::Enqueue(score)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(self.playlist.len() - 1)
+		}
+	}
+
+	/// Instruct the engine to move to the start of the given playlist item (in the order the items were queued).
+	///
+	/// Any notes left sounding are released as described by the current [`NoteRelease`] setting, and any loop region
+	/// is cleared.
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoSuchItem`] if the playlist does not contain the item.
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn skip_to_item(&mut self, item:usize) -> Result<(), Error> {
+		if item >= self.playlist.len() {
+			return Err(Error::NoSuchItem);
+		}
+
+		self.current_item = item;
+		self.position = 0;
+		self.loop_region = None;
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SkipToItem(item)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Instruct the engine to move to the start of the next playlist item (as determined by the shuffle and repeat
+	/// settings), or to stop playing if there is none.
+	///
+	/// The engine decides which item comes next, so the [Performer] reflects the change once the engine reports it
+	/// (through [`Performer::poll`] or [`Performer::poll_events`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn skip_to_next_item(&mut self) -> Result<(), Error> {
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SkipToNextItem) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Instruct the engine to play the rest of the playlist in a random order. Turning shuffling off returns to
+	/// the order in which the items were queued, continuing from the current item.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_shuffle(&mut self, shuffle:bool) -> Result<(), Error> {
+		self.shuffle = shuffle;
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetShuffle(shuffle)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Instruct the engine to return to the first item of the playlist after finishing the last (reshuffling the
+	/// order if shuffling).
+	///
+	/// # Errors
+	/// - Will return an [`Error::Communication`] if there is a communication issue with the engine.
+	pub fn set_repeat_all(&mut self, repeat_all:bool) -> Result<(), Error> {
+		self.repeat_all = repeat_all;
+
+		if let Err(err) = self.channel_to_engine.send(ToEngine::SetRepeatAll(repeat_all)) {
+			Err(Error::Communication(err))
+		} else {
+			Ok(())
+		}
+	}
 }
 
 impl Performer {
@@ -238,12 +1128,88 @@ impl Performer {
 	/// Assuming an "Ok" result, this method returns either;
 	/// - A "None" value, indicating that there are no midi messages to be addressed.
 	/// - A "Some" value, holding an vector of [`MidiEvent`]s along with the track number they are associated with.
+	///   (This vector will always be empty if the [Performer] was created with its own [Sink].)
+	///
+	/// Any other kinds of [Event] received are discarded; use [`Performer::poll_events`] to collect them.
 	///
 	/// # Errors
 	/// - Will return an [`Error::NoEngine`] if there is an issue with the engine thread handle.
 	/// - Will return an [`Error::Engine`] if engine has stopped and due to an issue encountered by the engine.
 	/// - Will return an [`Error::Thread`] if engine has stopped and there is an issue with "joining" the engine thread handle.
 	pub fn poll(&mut self) -> Result<Option<Vec<(usize, MidiEvent)>>, Error> {
+		Ok(
+			self.receive()?.map(|events| 
+				events
+					.into_iter()
+					.filter_map(|(track, event, _timestamp)| {
+						match event {
+							Event::Midi(midi_message) => Some((track, midi_message)),
+							_ => None
+						}
+					})
+					.collect()
+			)
+		)
+	}
+
+	/// Poll for playback messages from the engine, collecting every [Event] of the selected kinds
+	/// (including system exclusive, escape and meta events).
+	/// 
+	/// Assuming an "Ok" result, this method returns either;
+	/// - A "None" value, indicating that there are no events to be addressed.
+	/// - A "Some" value, holding an vector of [`Event`]s along with the track number they are associated with.
+	///   (This vector will always be empty if the [Performer] was created with its own [Sink].)
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoEngine`] if there is an issue with the engine thread handle.
+	/// - Will return an [`Error::Engine`] if engine has stopped and due to an issue encountered by the engine.
+	/// - Will return an [`Error::Thread`] if engine has stopped and there is an issue with "joining" the engine thread handle.
+	pub fn poll_events(&mut self, event_kinds:EventKinds) -> Result<Option<Vec<(usize, Event)>>, Error> {
+		Ok(
+			self.receive()?.map(|events| 
+				events
+					.into_iter()
+					.filter(|(_track, event, _timestamp)| event_kinds.includes(event))
+					.map(|(track, event, _timestamp)| (track, event))
+					.collect()
+			)
+		)
+	}
+
+	/// Poll for playback messages from the engine, collecting every [Event] of the selected kinds along with the
+	/// [Timestamp] it was scheduled for, so that late polling can be compensated for, or events scheduled ahead of
+	/// time (see [`Performer::set_lookahead`]).
+	/// 
+	/// Assuming an "Ok" result, this method returns either;
+	/// - A "None" value, indicating that there are no events to be addressed.
+	/// - A "Some" value, holding an vector of [`Event`]s along with the track number they are associated with and
+	///   their [Timestamp]s.
+	///   (This vector will always be empty if the [Performer] was created with its own [Sink].)
+	///
+	/// # Errors
+	/// - Will return an [`Error::NoEngine`] if there is an issue with the engine thread handle.
+	/// - Will return an [`Error::Engine`] if engine has stopped and due to an issue encountered by the engine.
+	/// - Will return an [`Error::Thread`] if engine has stopped and there is an issue with "joining" the engine thread handle.
+	pub fn poll_timestamped(&mut self, event_kinds:EventKinds) -> Result<Option<Vec<TimestampedEvent>>, Error> {
+		Ok(
+			self.receive()?.map(|events| 
+				events
+					.into_iter()
+					.filter(|(_track, event, _timestamp)| event_kinds.includes(event))
+					.collect()
+			)
+		)
+	}
+
+	/// Take the [Notification]s received from the engine by the last calls to [`Performer::poll`],
+	/// [`Performer::poll_events`] or [`Performer::poll_timestamped`].
+	///
+	/// (Notifications are delivered to the [Sink] instead if the [Performer] was created with its own.)
+	pub fn take_notifications(&mut self) -> Vec<Notification> {
+		std::mem::take(&mut self.notifications)
+	}
+
+	fn receive(&mut self) -> Result<Option<Vec<TimestampedEvent>>, Error> {
 		//engine check
 			let Some(engine_thread_handle) = &mut self.engine_thread_handle else {
 				return Err(Error::NoEngine);
@@ -268,11 +1234,10 @@ impl Performer {
 						.try_iter()
 						.filter_map(|message| {
 							match message {
-								ToConsole::Event(track, event) => {
-									match event {
-										Event::Midi(midi_message) => Some((track, midi_message)),
-										_ => None
-									}
+								ToConsole::Event(track, event, timestamp) => Some((track, event, timestamp)),
+								ToConsole::Notification(notification) => {
+									self.notifications.push(notification);
+									None
 								}
 								ToConsole::Stopped => {
 									self.is_playing = false;
@@ -282,6 +1247,20 @@ impl Performer {
 									self.position = new_position;
 									None
 								}
+								ToConsole::ItemUpdate(new_item) => {
+									self.current_item = new_item;
+									self.position = 0;
+									self.loop_region = None;
+									None
+								}
+								ToConsole::ItemReloaded(item, score, new_position) => {
+									self.playlist[item] = score;
+									if item == self.current_item {
+										self.position = new_position;
+										self.loop_region = None;
+									}
+									None
+								}
 							}
 						})
 						.collect()
@@ -0,0 +1,60 @@
+use crate::score::{Bbt, Score};
+
+/// How the playhead is placed when a new score replaces the current one with [`Performer::load`](crate::Performer::load).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum LoadPosition {
+	/// Return to the start of the new score.
+	#[default]
+	Start,
+	/// Keep the same tick position.
+	Tick,
+	/// Keep the same bar, beat and tick position, or failing that the start of the same bar.
+	/// (SMPTE timecode scores keep the same tick position instead.)
+	Bar,
+	/// Keep the same distance past the latest marker (or cue point) at or before the playhead, from the marker of the
+	/// same name and kind in the new score. If that distance would reach beyond the end of the new score, the playhead
+	/// is placed at the marker itself instead, and if the new score has no such marker, at its start.
+	Marker,
+}
+
+impl LoadPosition {
+	/// Find the position in the new score corresponding to the given position in the old one. Positions that cannot be
+	/// found, or that lie beyond the end of the new score, fall back to its start.
+	pub(crate) fn resolve(self, old_score:&Score, new_score:&Score, old_position:usize) -> usize {
+		let new_position = match self {
+			LoadPosition::Start => Some(0),
+			LoadPosition::Tick => Some(old_position),
+			LoadPosition::Bar => {
+				match old_score.ticks_to_bbt(old_position) {
+					Some(bbt) => new_score.bbt_to_ticks(bbt).or_else(|| new_score.bbt_to_ticks(Bbt::new(bbt.bar, 1, 0))),
+					None => Some(old_position)
+				}
+			},
+			LoadPosition::Marker => {
+				old_score.get_markers()
+					.iter()
+					.rfind(|marker| marker.tick <= old_position)
+					.and_then(|old_marker| {
+						new_score.get_markers()
+							.iter()
+							.find(|new_marker| new_marker.name == old_marker.name && new_marker.kind == old_marker.kind)
+							.map(|new_marker| {
+								let offset_position = new_marker.tick + (old_position - old_marker.tick);
+								if offset_position < new_score.len() {
+									offset_position
+								} else {
+									new_marker.tick
+								}
+							})
+					})
+			},
+		};
+
+		new_position
+			.filter(|new_position| *new_position < new_score.len())
+			.unwrap_or(0)
+	}
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,94 @@
+use midly::{num::{u15, u24, u28}, Format, Header, MetaMessage, Smf, Timing, TrackEvent, TrackEventKind};
+
+use super::LoadPosition;
+use crate::score::Score;
+
+// 480 ticks per beat, in 4/4
+static CONSTANT_TEMPO_MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/constant_tempo.mid");
+// 96 ticks per beat, with "Intro" at 0, "Verse" and the cue point "Lights" at 192 and "Chorus 2" at 384
+static MARKERS_MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/markers.mid");
+// 96 ticks per beat, with 4/4 from bar 1, 3/4 from bar 3, 6/8 from bar 5 and 2/4 from bar 6
+static METERS_MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/meters.mid");
+
+fn score(data:&[u8]) -> Score {
+	let standard_midi_file = midly::Smf::parse(data).unwrap();
+	Score::new(&standard_midi_file).ok().unwrap()
+}
+
+/// The markers of [`MARKERS_MID_FILE_DATA`] moved later: "Verse" and "Lights" at 400 and "Chorus 2" at 700, ending at
+/// 800.
+fn moved_markers_score() -> Score {
+	let meta = |delta:u32, message| TrackEvent { delta: u28::new(delta), kind: TrackEventKind::Meta(message) };
+	let standard_midi_file = Smf {
+		header: Header::new(Format::SingleTrack, Timing::Metrical(u15::new(96))),
+		tracks: vec![vec![
+			meta(0, MetaMessage::Tempo(u24::new(500_000))),
+			meta(0, MetaMessage::Marker(b"Intro")),
+			meta(400, MetaMessage::Marker(b"Verse")),
+			meta(0, MetaMessage::CuePoint(b"Lights")),
+			meta(300, MetaMessage::Marker(b"Chorus 2")),
+			meta(100, MetaMessage::EndOfTrack),
+		]],
+	};
+	Score::new(&standard_midi_file).ok().unwrap()
+}
+
+#[test]
+pub fn start() {
+	let old_score = score(CONSTANT_TEMPO_MID_FILE_DATA);
+	let new_score = score(METERS_MID_FILE_DATA);
+
+	assert_eq!(LoadPosition::Start.resolve(&old_score, &new_score, 1000), 0);
+}
+
+#[test]
+pub fn tick() {
+	let old_score = score(CONSTANT_TEMPO_MID_FILE_DATA);
+	let new_score = score(MARKERS_MID_FILE_DATA);
+
+	assert_eq!(LoadPosition::Tick.resolve(&old_score, &new_score, 500), 500);
+	//beyond the end of the new score
+		assert_eq!(LoadPosition::Tick.resolve(&old_score, &new_score, 1000), 0);
+}
+
+#[test]
+pub fn bar() {
+	let meters_score = score(METERS_MID_FILE_DATA);
+	let constant_tempo_score = score(CONSTANT_TEMPO_MID_FILE_DATA);
+	let markers_score = score(MARKERS_MID_FILE_DATA);
+
+	//3.2.10
+		assert_eq!(LoadPosition::Bar.resolve(&meters_score, &constant_tempo_score, 874), 2 * 1920 + 480 + 10);
+	//5.3.47
+		assert_eq!(LoadPosition::Bar.resolve(&meters_score, &constant_tempo_score, 1487), 4 * 1920 + 960 + 47);
+	//3.4.0 doesn't exist in 3/4, so the start of bar 3
+		assert_eq!(LoadPosition::Bar.resolve(&constant_tempo_score, &meters_score, 2 * 1920 + 1440), 768);
+	//bar 8 is beyond the end of the new score
+		assert_eq!(LoadPosition::Bar.resolve(&constant_tempo_score, &markers_score, 7 * 1920), 0);
+}
+
+#[test]
+pub fn marker() {
+	let markers_score = score(MARKERS_MID_FILE_DATA);
+	let constant_tempo_score = score(CONSTANT_TEMPO_MID_FILE_DATA);
+
+	assert_eq!(LoadPosition::Marker.resolve(&markers_score, &markers_score, 200), 200);
+	assert_eq!(LoadPosition::Marker.resolve(&markers_score, &markers_score, 576), 576);
+	//no markers in the old score
+		assert_eq!(LoadPosition::Marker.resolve(&constant_tempo_score, &markers_score, 200), 0);
+	//no markers in the new score
+		assert_eq!(LoadPosition::Marker.resolve(&markers_score, &constant_tempo_score, 200), 0);
+}
+
+#[test]
+pub fn marker_in_another_score() {
+	let markers_score = score(MARKERS_MID_FILE_DATA);
+	let moved_markers_score = moved_markers_score();
+
+	//8 ticks past "Verse"
+		assert_eq!(LoadPosition::Marker.resolve(&markers_score, &moved_markers_score, 200), 408);
+	//8 ticks past "Verse", back the other way
+		assert_eq!(LoadPosition::Marker.resolve(&moved_markers_score, &markers_score, 408), 200);
+	//192 ticks past "Chorus 2" is beyond the end of the new score, so "Chorus 2" itself
+		assert_eq!(LoadPosition::Marker.resolve(&markers_score, &moved_markers_score, 576), 700);
+}
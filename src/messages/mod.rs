@@ -1,8 +1,15 @@
-use crate::score::Event;
+use std::{sync::Arc, time::Duration};
+
+use midly::num::u4;
+
+use crate::{engine::{LoopRegion, NoteRelease, Transpose, VelocitySettings}, load_position::LoadPosition, notification::Notification, processor::{Processor, ProcessorId}, score::{Chase, Event, Score}, timestamp::Timestamp};
 
 pub enum ToConsole {
-	Event(usize, Event),
+	Event(usize, Event, Timestamp),
+	Notification(Notification),
 	PositionUpdate(usize),
+	ItemUpdate(usize),
+	ItemReloaded(usize, Arc<Score>, usize),
 	Stopped
 }
 
@@ -14,5 +21,25 @@ pub enum ToEngine {
 	Stop,
 	JumpTo(usize),
 	SetLooping(bool),
-	SetSpeed(f32)
+	SetLoopRegion(Option<LoopRegion>),
+	SetSpeed(f32),
+	SetNoteRelease(NoteRelease),
+	SetChase(Chase),
+	SetLookahead(Duration),
+	MuteTrack(usize, bool),
+	SoloTrack(usize, bool),
+	SetTranspose(Transpose),
+	SetVelocity(VelocitySettings),
+	MuteChannel(u4, bool),
+	SoloChannel(u4, bool),
+	RemapChannel(u4, u4),
+	InsertProcessor(usize, ProcessorId, Box<dyn Processor>),
+	RemoveProcessor(ProcessorId),
+	MoveProcessor(ProcessorId, usize),
+	Load(Arc<Score>, LoadPosition),
+	Enqueue(Arc<Score>),
+	SkipToItem(usize),
+	SkipToNextItem,
+	SetShuffle(bool),
+	SetRepeatAll(bool)
 }
\ No newline at end of file
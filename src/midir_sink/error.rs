@@ -0,0 +1,13 @@
+use midir::{ConnectErrorKind, InitError, PortInfoError};
+
+#[derive(Debug)]
+pub enum Error {
+	/// The midir output client could not be created.
+	Init(InitError),
+	/// The details of an output port could not be retrieved.
+	PortInfo(PortInfoError),
+	/// No output port matches the requested name or index.
+	NoSuchPort,
+	/// The connection to an output port (or the creation of a virtual port) failed.
+	Connect(ConnectErrorKind)
+}
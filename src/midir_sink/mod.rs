@@ -0,0 +1,142 @@
+use midir::{MidiOutput, MidiOutputConnection};
+
+use crate::{
+	score::{Event, MidiEvent},
+	sink::{Sink, SinkError}
+};
+
+mod error;
+pub use error::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// A [Sink] which sends the events of a playing score to a MIDI output port, using the [midir] crate.
+pub struct MidirSink {
+	connection: MidiOutputConnection
+}
+
+impl MidirSink {
+	/// List the names of all available MIDI output ports, in the order used by [`MidirSink::connect_to_index`].
+	///
+	/// # Errors
+	/// - Will return an [`Error::Init`] if the midir output client could not be created.
+	/// - Will return an [`Error::PortInfo`] if the name of a port could not be retrieved.
+	pub fn list_ports(client_name:&str) -> Result<Vec<String>, crate::Error> {
+		let midi_output = MidiOutput::new(client_name).map_err(Error::Init)?;
+
+		midi_output
+			.ports()
+			.iter()
+			.map(|port| midi_output.port_name(port).map_err(|err| Error::PortInfo(err).into()))
+			.collect()
+	}
+
+	/// Connect to the MIDI output port with the given name. If no port has exactly that name, the first port whose
+	/// name contains it is used instead.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Init`] if the midir output client could not be created.
+	/// - Will return an [`Error::PortInfo`] if the name of a port could not be retrieved.
+	/// - Will return an [`Error::NoSuchPort`] if no port matches the given name.
+	/// - Will return an [`Error::Connect`] if the connection to the port fails.
+	pub fn connect(client_name:&str, port_name:&str) -> Result<MidirSink, crate::Error> {
+		let port_names = MidirSink::list_ports(client_name)?;
+		let index = find_port(&port_names, port_name).ok_or(Error::NoSuchPort)?;
+
+		MidirSink::connect_to_index(client_name, index)
+	}
+
+	/// Connect to the MIDI output port with the given index (as listed by [`MidirSink::list_ports`]).
+	///
+	/// # Errors
+	/// - Will return an [`Error::Init`] if the midir output client could not be created.
+	/// - Will return an [`Error::NoSuchPort`] if there is no port with the given index.
+	/// - Will return an [`Error::Connect`] if the connection to the port fails.
+	pub fn connect_to_index(client_name:&str, index:usize) -> Result<MidirSink, crate::Error> {
+		let midi_output = MidiOutput::new(client_name).map_err(Error::Init)?;
+		let port = midi_output.ports().into_iter().nth(index).ok_or(Error::NoSuchPort)?;
+
+		Ok(
+			MidirSink {
+				connection: midi_output.connect(&port, client_name).map_err(|err| Error::Connect(err.kind()))?
+			}
+		)
+	}
+
+	/// Create a virtual MIDI output port with the given name, to which other applications can connect.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Init`] if the midir output client could not be created.
+	/// - Will return an [`Error::Connect`] if the virtual port could not be created.
+	#[cfg(unix)]
+	pub fn create_virtual(client_name:&str, port_name:&str) -> Result<MidirSink, crate::Error> {
+		use midir::os::unix::VirtualOutput;
+
+		let midi_output = MidiOutput::new(client_name).map_err(Error::Init)?;
+
+		Ok(
+			MidirSink {
+				connection: midi_output.create_virtual(port_name).map_err(|err| Error::Connect(err.kind()))?
+			}
+		)
+	}
+}
+
+impl MidirSink {
+	/// Close the connection, returning the underlying [`MidiOutputConnection`].
+	pub fn into_connection(self) -> MidiOutputConnection {
+		self.connection
+	}
+}
+
+impl From<MidiOutputConnection> for MidirSink {
+	fn from(connection:MidiOutputConnection) -> MidirSink {
+		MidirSink {
+			connection
+		}
+	}
+}
+
+impl Sink for MidirSink {
+	fn receive(&mut self, _track:usize, midi_event:MidiEvent) -> Result<(), SinkError> {
+		self.connection.send(&midi_event.encode())?;
+		Ok(())
+	}
+
+	fn receive_sysex(&mut self, _track:usize, data:&[u8]) -> Result<(), SinkError> {
+		self.connection.send(&encode_sysex(data))?;
+		Ok(())
+	}
+
+	fn receive_event(&mut self, track:usize, event:&Event) -> Result<(), SinkError> {
+		match event {
+			Event::Midi(midi_event) => self.receive(track, *midi_event),
+			Event::SysEx(data) => self.receive_sysex(track, data),
+			Event::Escape(data) => {
+				self.connection.send(data)?;
+				Ok(())
+			},
+			Event::Meta(_) => Ok(())
+		}
+	}
+}
+
+/// The index of the port with the given name or, failing that, of the first port whose name contains it.
+fn find_port(port_names:&[String], port_name:&str) -> Option<usize> {
+	port_names
+		.iter()
+		.position(|name| name == port_name)
+		.or_else(|| port_names.iter().position(|name| name.contains(port_name)))
+}
+
+/// Frame the data of a system exclusive message with its status byte and (if missing) its end byte.
+fn encode_sysex(data:&[u8]) -> Vec<u8> {
+	let mut message = Vec::with_capacity(data.len() + 2);
+	message.push(0xF0);
+	message.extend_from_slice(data);
+	if message.last() != Some(&0xF7) {
+		message.push(0xF7);
+	}
+	message
+}
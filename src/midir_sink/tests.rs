@@ -0,0 +1,42 @@
+use midly::{num::{u14, u4, u7}, MidiMessage, PitchBend};
+
+use super::{encode_sysex, find_port};
+use crate::score::MidiEvent;
+
+fn port_names(names:&[&str]) -> Vec<String> {
+	names.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+pub fn find_port_by_exact_name() {
+	let port_names = port_names(&["Midi Through Port-0", "Synth", "Synth Port-1"]);
+	assert_eq!(find_port(&port_names, "Synth"), Some(1));
+	assert_eq!(find_port(&port_names, "Synth Port-1"), Some(2));
+}
+
+#[test]
+pub fn find_port_by_partial_name() {
+	let port_names = port_names(&["Midi Through Port-0", "FLUID Synth (1234):Synth input port"]);
+	assert_eq!(find_port(&port_names, "FLUID"), Some(1));
+	assert_eq!(find_port(&port_names, "Port"), Some(0));
+	assert_eq!(find_port(&port_names, "Keyboard"), None);
+	assert_eq!(find_port(&[], "FLUID"), None);
+}
+
+#[test]
+pub fn encode_channel_messages() {
+	let note_on = MidiEvent { channel: u4::new(3), message: MidiMessage::NoteOn { key: u7::new(60), vel: u7::new(100) } };
+	assert_eq!(note_on.encode(), [0x93, 60, 100]);
+
+	let program_change = MidiEvent { channel: u4::new(9), message: MidiMessage::ProgramChange { program: u7::new(5) } };
+	assert_eq!(program_change.encode(), [0xC9, 5]);
+
+	let pitch_bend = MidiEvent { channel: u4::new(0), message: MidiMessage::PitchBend { bend: PitchBend(u14::new(0x3000)) } };
+	assert_eq!(pitch_bend.encode(), [0xE0, 0x00, 0x60]);
+}
+
+#[test]
+pub fn encode_sysex_messages() {
+	assert_eq!(encode_sysex(&[0x7E, 0x7F, 0x09, 0x01, 0xF7]), [0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7]);
+	assert_eq!(encode_sysex(&[0x7E, 0x7F, 0x09, 0x01]), [0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7]);
+}
@@ -0,0 +1,16 @@
+use crate::processor::ProcessorId;
+
+/// A notice about the progress of playback, sent by the engine alongside the score's events.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Notification {
+	/// The syllable with the given index (into [`Lyrics::get_syllables`](crate::Lyrics::get_syllables)) has become current.
+	Syllable(usize),
+	/// Playback has passed the marker with the given index (into [`Performer::get_markers`](crate::Performer::get_markers)).
+	Marker(usize),
+	/// Playback has returned to the start of the loop region, for the given number of times so far.
+	LoopWrapped(usize),
+	/// The playlist item with the given index (in the order it was queued, counting from 0) has begun playing.
+	ItemStarted(usize),
+	/// The engine was asked to remove or move a [`Processor`](crate::Processor) which is not in its pipeline.
+	NoSuchProcessor(ProcessorId),
+}
@@ -0,0 +1,82 @@
+use midly::num::u4;
+
+use crate::score::{Event, EventKinds};
+
+use super::Processor;
+
+type CustomPredicate = dyn FnMut(usize, &Event) -> bool + Send;
+
+/// Which events a [`FilterProcessor`] keeps.
+enum Predicate {
+	Custom(Box<CustomPredicate>),
+	EventKinds(EventKinds),
+	Tracks(Vec<usize>),
+	Channels(Vec<u4>),
+}
+
+/// A [Processor] which drops every [Event] that doesn't satisfy its predicate.
+///
+/// Take care when dropping "Note Off" messages, as the notes they end will be left hanging.
+pub struct FilterProcessor {
+	predicate: Predicate,
+}
+
+impl FilterProcessor {
+	/// A [`FilterProcessor`] which keeps the events (from the track with the given index) for which the predicate
+	/// returns true.
+	///
+	/// As with closures, such a filter cannot be duplicated (see [`Processor::try_clone`]).
+	pub fn new(predicate:impl FnMut(usize, &Event) -> bool + Send + 'static) -> FilterProcessor {
+		FilterProcessor {
+			predicate: Predicate::Custom(Box::new(predicate))
+		}
+	}
+
+	/// A [`FilterProcessor`] which keeps only the selected kinds of event.
+	pub fn event_kinds(event_kinds:EventKinds) -> FilterProcessor {
+		FilterProcessor {
+			predicate: Predicate::EventKinds(event_kinds)
+		}
+	}
+
+	/// A [`FilterProcessor`] which keeps only the events of the given tracks.
+	pub fn tracks(tracks:&[usize]) -> FilterProcessor {
+		FilterProcessor {
+			predicate: Predicate::Tracks(tracks.to_vec())
+		}
+	}
+
+	/// A [`FilterProcessor`] which drops the [`Event::Midi`] events of every channel but the given ones.
+	pub fn channels(channels:&[u4]) -> FilterProcessor {
+		FilterProcessor {
+			predicate: Predicate::Channels(channels.to_vec())
+		}
+	}
+}
+
+impl Processor for FilterProcessor {
+	fn process(&mut self, track:usize, event:Event) -> Option<Event> {
+		let keep = match &mut self.predicate {
+			Predicate::Custom(predicate) => predicate(track, &event),
+			Predicate::EventKinds(event_kinds) => event_kinds.includes(&event),
+			Predicate::Tracks(tracks) => tracks.contains(&track),
+			Predicate::Channels(channels) => match &event {
+				Event::Midi(midi_event) => channels.contains(&midi_event.channel),
+				_ => true,
+			},
+		};
+
+		keep.then_some(event)
+	}
+
+	fn try_clone(&self) -> Option<Box<dyn Processor>> {
+		let predicate = match &self.predicate {
+			Predicate::Custom(_) => return None,
+			Predicate::EventKinds(event_kinds) => Predicate::EventKinds(*event_kinds),
+			Predicate::Tracks(tracks) => Predicate::Tracks(tracks.clone()),
+			Predicate::Channels(channels) => Predicate::Channels(channels.clone()),
+		};
+
+		Some(Box::new(FilterProcessor { predicate }))
+	}
+}
@@ -0,0 +1,58 @@
+use crate::score::Event;
+
+mod filter;
+pub use filter::FilterProcessor;
+mod remap;
+pub use remap::RemapProcessor;
+mod transpose;
+pub use transpose::TransposeProcessor;
+mod velocity;
+pub use velocity::VelocityProcessor;
+
+#[cfg(test)]
+mod tests;
+
+/// A step of the pipeline each [Event] passes through in the engine thread, after the [`Performer`](crate::Performer)'s
+/// own settings (mute, transposition, etc.) have been applied and before it is sent to the [`Sink`](crate::Sink).
+///
+/// Processors are added to the pipeline (and removed or reordered) with
+/// [`Performer::add_processor`](crate::Performer::add_processor) and its neighbours, and run in order, each receiving
+/// the output of the one before.
+pub trait Processor: Send {
+	/// Process an [Event] from the track with the given index, returning the event to send on in its place, or `None`
+	/// to drop it.
+	///
+	/// This is called from the engine thread, so any time spent here delays the events that follow.
+	fn process(&mut self, track:usize, event:Event) -> Option<Event>;
+
+	/// Forget about any notes being followed, as the engine has just released every sounding note (on pausing,
+	/// stopping, jumping, looping, or changing the pipeline).
+	///
+	/// By default, this does nothing.
+	fn reset(&mut self) {}
+
+	/// Duplicate the processor, so that [`Performer::render`](crate::Performer::render) can run a copy of the pipeline
+	/// of its own. The copy is made as the processor is added to the pipeline, before it has processed any events.
+	///
+	/// By default (and for closures), processors cannot be duplicated, and a performance with one in its pipeline
+	/// cannot be rendered.
+	fn try_clone(&self) -> Option<Box<dyn Processor>> {
+		None
+	}
+}
+
+impl<F> Processor for F where F: FnMut(usize, Event) -> Option<Event> + Send {
+	fn process(&mut self, track:usize, event:Event) -> Option<Event> {
+		self(track, event)
+	}
+}
+
+impl std::fmt::Debug for dyn Processor {
+	fn fmt(&self, formatter:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		formatter.write_str("Processor")
+	}
+}
+
+/// Identifies a [Processor] in the pipeline, for removing or moving it later.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProcessorId(pub(crate) usize);
@@ -0,0 +1,41 @@
+use midly::num::u4;
+
+use crate::{engine::ChannelMap, score::Event};
+
+use super::Processor;
+
+/// A [Processor] which moves every [`Event::Midi`] event of some channels to others.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RemapProcessor {
+	channel_map: ChannelMap,
+}
+
+impl RemapProcessor {
+	/// A [`RemapProcessor`] which leaves every channel where it is, until [`RemapProcessor::remap`] is called.
+	pub fn new() -> RemapProcessor {
+		RemapProcessor::default()
+	}
+
+	/// Send the events of a channel to another channel instead.
+	#[must_use]
+	pub fn remap(mut self, channel:u4, destination:u4) -> RemapProcessor {
+		self.channel_map.set(channel, destination);
+		self
+	}
+}
+
+impl Processor for RemapProcessor {
+	fn process(&mut self, _track:usize, event:Event) -> Option<Event> {
+		match event {
+			Event::Midi(mut midi_event) => {
+				midi_event.channel = self.channel_map.get(midi_event.channel);
+				Some(Event::Midi(midi_event))
+			},
+			event => Some(event),
+		}
+	}
+
+	fn try_clone(&self) -> Option<Box<dyn Processor>> {
+		Some(Box::new(self.clone()))
+	}
+}
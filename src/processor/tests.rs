@@ -0,0 +1,145 @@
+use midly::{num::{u4, u7}, MidiMessage};
+
+use super::{FilterProcessor, Processor, RemapProcessor, TransposeProcessor, VelocityProcessor};
+use crate::{engine::Velocity, score::{Event, EventKinds, MidiEvent}, Error, Performer};
+
+static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/scarborough_fair.mid");
+
+fn note_on(channel:u8, key:u8) -> Event {
+	Event::Midi(MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(100) } })
+}
+fn note_off(channel:u8, key:u8) -> Event {
+	Event::Midi(MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOff { key: u7::new(key), vel: u7::new(0) } })
+}
+fn sysex() -> Event {
+	Event::SysEx(vec![0x7E, 0x7F, 0x09, 0x01, 0xF7])
+}
+
+#[test]
+pub fn closures() {
+	let mut count = 0;
+	let mut processor = move |_track, event| {
+		count += 1;
+		(count % 2 == 1).then_some(event)
+	};
+
+	assert_eq!(processor.process(0, note_on(0, 60)), Some(note_on(0, 60)));
+	assert_eq!(processor.process(0, note_on(0, 62)), None);
+	assert_eq!(processor.process(0, note_on(0, 64)), Some(note_on(0, 64)));
+}
+
+#[test]
+pub fn filter() {
+	let mut processor = FilterProcessor::event_kinds(EventKinds::midi_only());
+	assert_eq!(processor.process(0, note_on(0, 60)), Some(note_on(0, 60)));
+	assert_eq!(processor.process(0, sysex()), None);
+
+	let mut processor = FilterProcessor::tracks(&[1, 2]);
+	assert_eq!(processor.process(0, note_on(0, 60)), None);
+	assert_eq!(processor.process(2, note_on(0, 60)), Some(note_on(0, 60)));
+
+	// (events without a channel are kept)
+	let mut processor = FilterProcessor::channels(&[u4::new(3)]);
+	assert_eq!(processor.process(0, note_on(0, 60)), None);
+	assert_eq!(processor.process(0, note_on(3, 60)), Some(note_on(3, 60)));
+	assert_eq!(processor.process(0, sysex()), Some(sysex()));
+}
+
+#[test]
+pub fn remap() {
+	let mut processor = RemapProcessor::new().remap(u4::new(0), u4::new(5)).remap(u4::new(5), u4::new(0));
+	assert_eq!(processor.process(0, note_on(0, 60)), Some(note_on(5, 60)));
+	assert_eq!(processor.process(0, note_on(5, 60)), Some(note_on(0, 60)));
+	assert_eq!(processor.process(0, note_on(1, 60)), Some(note_on(1, 60)));
+	assert_eq!(processor.process(0, sysex()), Some(sysex()));
+}
+
+#[test]
+pub fn transpose() {
+	let mut processor = TransposeProcessor::new(2);
+	assert_eq!(processor.process(0, note_on(0, 60)), Some(note_on(0, 62)));
+	assert_eq!(processor.process(0, note_on(9, 36)), Some(note_on(9, 36)));
+	assert_eq!(processor.process(0, note_off(0, 60)), Some(note_off(0, 62)));
+
+	let mut processor = TransposeProcessor::new(2).with_drums(true);
+	assert_eq!(processor.process(0, note_on(9, 36)), Some(note_on(9, 38)));
+}
+
+#[test]
+pub fn velocity() {
+	let mut processor = VelocityProcessor::new(Velocity::fixed(u7::new(64)));
+	assert_eq!(
+		processor.process(0, note_on(0, 60)),
+		Some(Event::Midi(MidiEvent { channel: u4::new(0), message: MidiMessage::NoteOn { key: u7::new(60), vel: u7::new(64) } }))
+	);
+	assert_eq!(processor.process(0, note_off(0, 60)), Some(note_off(0, 60)));
+}
+
+/// A processor which drops every other event.
+#[derive(Default, Clone)]
+struct Alternate {
+	count: usize,
+}
+
+impl Processor for Alternate {
+	fn process(&mut self, _track:usize, event:Event) -> Option<Event> {
+		self.count += 1;
+		(self.count % 2 == 1).then_some(event)
+	}
+
+	fn try_clone(&self) -> Option<Box<dyn Processor>> {
+		Some(Box::new(self.clone()))
+	}
+}
+
+#[test]
+pub fn rendered_with_copies() {
+	let mut performer = Performer::new(midly::Smf::parse(MID_FILE_DATA).unwrap());
+	let unprocessed:Vec<_> = performer.render().unwrap().collect();
+
+	performer.add_processor(Alternate::default()).unwrap();
+	performer.add_processor(TransposeProcessor::new(2)).unwrap();
+
+	// (each render runs fresh copies of the processors, so they agree with each other and with the processors run by hand)
+	let mut filter = Alternate::default();
+	let mut transpose = TransposeProcessor::new(2);
+	let expected:Vec<_> = unprocessed
+		.into_iter()
+		.filter_map(|(time, track, event)| filter.process(track, event).and_then(|event| transpose.process(track, event)).map(|event| (time, track, event)))
+		.collect();
+	assert_eq!(performer.render().unwrap().collect::<Vec<_>>(), expected);
+	assert_eq!(performer.render().unwrap().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+pub fn uncloneable_processors_are_not_rendered() {
+	struct Uncloneable;
+	impl Processor for Uncloneable {
+		fn process(&mut self, _track:usize, event:Event) -> Option<Event> {
+			Some(event)
+		}
+	}
+
+	let mut performer = Performer::new(midly::Smf::parse(MID_FILE_DATA).unwrap());
+	let id = performer.add_processor(Uncloneable).unwrap();
+	assert!(matches!(performer.render(), Err(Error::UncloneableProcessor)));
+	performer.remove_processor(id).unwrap();
+	assert!(performer.render().is_ok());
+
+	// (closures, stateful or not, make processors too, but can't be duplicated)
+	let (sender, receiver) = crossbeam_channel::unbounded();
+	let id = performer.add_processor(move |_track, event:Event| {
+		sender.send(event.clone()).ok()?;
+		Some(event)
+	}).unwrap();
+	assert!(matches!(performer.render(), Err(Error::UncloneableProcessor)));
+	performer.remove_processor(id).unwrap();
+	assert!(receiver.is_empty());
+
+	let id = performer.add_processor(FilterProcessor::new(|_track, _event| true)).unwrap();
+	assert!(matches!(performer.render(), Err(Error::UncloneableProcessor)));
+	performer.remove_processor(id).unwrap();
+
+	performer.add_processor(FilterProcessor::tracks(&[0])).unwrap();
+	assert!(performer.render().is_ok());
+}
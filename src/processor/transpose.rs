@@ -0,0 +1,54 @@
+use crate::{engine::{OutOfRangeNotes, Transpose, TransposedNotes}, score::Event};
+
+use super::Processor;
+
+/// A [Processor] which shifts notes up or down by a number of semitones, ending each note at the key it began at.
+///
+/// As with [`Performer::set_transpose`](crate::Performer::set_transpose), the drum channel (channel 10) is left
+/// alone unless requested otherwise.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransposeProcessor {
+	transpose: Transpose,
+	transposed_notes: TransposedNotes,
+}
+
+impl TransposeProcessor {
+	/// A [`TransposeProcessor`] which shifts notes by the given number of semitones.
+	pub fn new(semitones:i8) -> TransposeProcessor {
+		TransposeProcessor {
+			transpose: Transpose { semitones, ..Transpose::default() },
+			transposed_notes: TransposedNotes::default()
+		}
+	}
+
+	/// Select whether the drum channel is transposed too.
+	#[must_use]
+	pub fn with_drums(mut self, transpose_drums:bool) -> TransposeProcessor {
+		self.transpose.drums = transpose_drums;
+		self
+	}
+
+	/// Select what happens to notes transposed beyond the range of MIDI keys.
+	#[must_use]
+	pub fn with_out_of_range_notes(mut self, out_of_range_notes:OutOfRangeNotes) -> TransposeProcessor {
+		self.transpose.out_of_range_notes = out_of_range_notes;
+		self
+	}
+}
+
+impl Processor for TransposeProcessor {
+	fn process(&mut self, track:usize, event:Event) -> Option<Event> {
+		match event {
+			Event::Midi(midi_event) => self.transposed_notes.apply(&self.transpose, track, midi_event).map(Event::Midi),
+			event => Some(event),
+		}
+	}
+
+	fn reset(&mut self) {
+		self.transposed_notes.clear();
+	}
+
+	fn try_clone(&self) -> Option<Box<dyn Processor>> {
+		Some(Box::new(self.clone()))
+	}
+}
@@ -0,0 +1,32 @@
+use crate::{engine::{Velocity, VelocitySettings}, score::Event};
+
+use super::Processor;
+
+/// A [Processor] which changes the velocities of notes (or, depending on its
+/// [`VelocityTarget`](crate::VelocityTarget), the volume of channels).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VelocityProcessor {
+	velocity: VelocitySettings,
+}
+
+impl VelocityProcessor {
+	/// A [`VelocityProcessor`] which applies the given [Velocity] transform to every track.
+	pub fn new(velocity:Velocity) -> VelocityProcessor {
+		VelocityProcessor {
+			velocity: VelocitySettings { global: velocity, ..VelocitySettings::default() }
+		}
+	}
+}
+
+impl Processor for VelocityProcessor {
+	fn process(&mut self, track:usize, event:Event) -> Option<Event> {
+		match event {
+			Event::Midi(midi_event) => Some(Event::Midi(self.velocity.apply_to(track, midi_event))),
+			event => Some(event),
+		}
+	}
+
+	fn try_clone(&self) -> Option<Box<dyn Processor>> {
+		Some(Box::new(self.clone()))
+	}
+}
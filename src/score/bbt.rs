@@ -0,0 +1,133 @@
+use std::fmt;
+
+use super::{Event, MetaEvent, Track};
+
+/// A musical position, in bars, beats and ticks.
+///
+/// Bars and beats are counted from 1, while ticks (within the beat) are counted from 0. A beat is the note value given by
+/// the denominator of the time signature in effect (eg. an eighth note in 6/8).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bbt {
+	pub bar: usize,
+	pub beat: usize,
+	pub tick: usize,
+}
+
+impl Bbt {
+	pub fn new(bar:usize, beat:usize, tick:usize) -> Bbt {
+		Bbt {
+			bar,
+			beat,
+			tick
+		}
+	}
+}
+
+impl fmt::Display for Bbt {
+	fn fmt(&self, formatter:&mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(formatter, "{}.{}.{}", self.bar, self.beat, self.tick)
+	}
+}
+
+/// A stretch of a score in which a single time signature is in effect.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(super) struct Meter {
+	/// The tick this meter begins at.
+	tick: usize,
+	/// The (1-based) bar this meter begins at.
+	bar: usize,
+	beats_per_bar: usize,
+	ticks_per_beat: usize,
+}
+
+impl Meter {
+	fn ticks_per_bar(&self) -> usize {
+		self.beats_per_bar * self.ticks_per_beat
+	}
+
+	/// Build the meters of a metrical score from its time signature messages. A score without a time signature at its
+	/// beginning is assumed to start in 4/4. A time signature change in the middle of a bar begins a new bar.
+	pub(super) fn collect(tracks:&[Track], ticks_per_quarter_note:u16) -> Vec<Meter> {
+		let mut time_signatures:Vec<(usize, u8, u8)> = tracks
+			.iter()
+			.flat_map(|track|
+				track
+					.iter_events()
+					.filter_map(|(tick, event)| {
+						if let Event::Meta(MetaEvent::TimeSignature(numerator, denominator, _, _)) = event {
+							Some((tick, *numerator, *denominator))
+						} else {
+							None
+						}
+					})
+			)
+			.collect();
+		time_signatures.sort_by_key(|(tick, _, _)| *tick);
+		if time_signatures.first().is_none_or(|(tick, _, _)| *tick != 0) {
+			time_signatures.insert(0, (0, 4, 2));
+		}
+
+		let mut meters:Vec<Meter> = vec![];
+		for (tick, numerator, denominator) in time_signatures {
+			let ticks_per_beat = (usize::from(ticks_per_quarter_note) * 4)
+				.checked_shr(u32::from(denominator))
+				.unwrap_or(0)
+				.max(1);
+			let beats_per_bar = usize::from(numerator).max(1);
+
+			let bar = match meters.last() {
+				//a later time signature at the same tick replaces the earlier one
+					Some(last) if last.tick == tick => {
+						let bar = last.bar;
+						meters.pop();
+						bar
+					},
+				Some(last) => last.bar + (tick - last.tick).div_ceil(last.ticks_per_bar()),
+				None => 1
+			};
+
+			meters.push(
+				Meter {
+					tick,
+					bar,
+					beats_per_bar,
+					ticks_per_beat
+				}
+			);
+		}
+
+		meters
+	}
+
+	/// Convert a tick position to a [Bbt] position.
+	pub(super) fn ticks_to_bbt(meters:&[Meter], tick:usize) -> Option<Bbt> {
+		let meter = meters[..meters.partition_point(|meter| meter.tick <= tick)].last()?;
+		let ticks_into_meter = tick - meter.tick;
+
+		Some(
+			Bbt {
+				bar: meter.bar + ticks_into_meter / meter.ticks_per_bar(),
+				beat: 1 + (ticks_into_meter % meter.ticks_per_bar()) / meter.ticks_per_beat,
+				tick: ticks_into_meter % meter.ticks_per_beat,
+			}
+		)
+	}
+
+	/// Convert a [Bbt] position to a tick position, returning `None` if the beat or tick lies outside of its bar.
+	pub(super) fn bbt_to_ticks(meters:&[Meter], bbt:Bbt) -> Option<usize> {
+		let meter = meters[..meters.partition_point(|meter| meter.bar <= bbt.bar)].last()?;
+
+		if bbt.beat == 0 || bbt.beat > meter.beats_per_bar || bbt.tick >= meter.ticks_per_beat {
+			return None;
+		}
+
+		let tick = meter.tick + (bbt.bar - meter.bar) * meter.ticks_per_bar() + (bbt.beat - 1) * meter.ticks_per_beat + bbt.tick;
+
+		//a bar cut short by the following time signature change
+			if meters.iter().any(|later_meter| later_meter.tick > meter.tick && later_meter.tick <= tick) {
+				return None;
+			}
+
+		Some(tick)
+	}
+}
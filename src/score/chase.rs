@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use midly::{num::{u4, u7}, MidiMessage, PitchBend};
+
+use super::MidiEvent;
+
+const BANK_SELECT_CONTROLLERS:[u8; 2] = [0, 32];
+// data entry, increment/decrement and (N)RPN selection messages only make sense in sequence, so they are not chased
+const UNCHASED_CONTROLLERS:[u8; 8] = [6, 38, 96, 97, 98, 99, 100, 101];
+// controllers 120 to 127 are channel mode messages (all notes off, reset, etc.) rather than state
+const FIRST_CHANNEL_MODE_CONTROLLER:u8 = 120;
+
+/// The kinds of channel state the engine reconstructs and sends when playback jumps into the middle of a score
+/// (or loops back to its start), so that the music continues with the correct instruments and settings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Chase {
+	/// Chase program changes (along with any bank select messages).
+	pub program_changes: bool,
+	/// Chase controller values (eg. volume, pan, sustain). Channel mode messages and (N)RPN data entry controllers are not chased.
+	pub controllers: bool,
+	/// Chase pitch bend values.
+	pub pitch_bend: bool,
+	/// Chase channel pressure (channel aftertouch) values.
+	pub channel_pressure: bool,
+}
+
+impl Default for Chase {
+	fn default() -> Chase {
+		Chase::none()
+	}
+}
+
+impl Chase {
+	/// A [`Chase`] which reconstructs nothing.
+	pub fn none() -> Chase {
+		Chase {
+			program_changes: false,
+			controllers: false,
+			pitch_bend: false,
+			channel_pressure: false
+		}
+	}
+
+	/// A [`Chase`] which reconstructs every kind of channel state.
+	pub fn all() -> Chase {
+		Chase {
+			program_changes: true,
+			controllers: true,
+			pitch_bend: true,
+			channel_pressure: true
+		}
+	}
+
+	pub(super) fn is_none(&self) -> bool {
+		*self == Chase::none()
+	}
+}
+
+/// The latest state of a single channel.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct ChannelState {
+	program: Option<u7>,
+	controllers: BTreeMap<u7, u7>,
+	pitch_bend: Option<PitchBend>,
+	channel_pressure: Option<u7>
+}
+
+impl ChannelState {
+	pub fn register(&mut self, message:&MidiMessage) {
+		match message {
+			MidiMessage::ProgramChange { program } => self.program = Some(*program),
+			MidiMessage::Controller { controller, value } if u8::from(*controller) < FIRST_CHANNEL_MODE_CONTROLLER && !UNCHASED_CONTROLLERS.contains(&u8::from(*controller)) => {
+				self.controllers.insert(*controller, *value);
+			},
+			MidiMessage::PitchBend { bend } => self.pitch_bend = Some(*bend),
+			MidiMessage::ChannelAftertouch { vel } => self.channel_pressure = Some(*vel),
+			_ => {}
+		}
+	}
+
+	/// Produce the events which recreate this state, with any bank select messages preceding the program change.
+	pub fn into_events(self, channel:u4, chase:Chase) -> Vec<MidiEvent> {
+		let (bank_select_controllers, other_controllers):(BTreeMap<u7, u7>, BTreeMap<u7, u7>) = self.controllers
+			.into_iter()
+			.partition(|(controller, _)| BANK_SELECT_CONTROLLERS.contains(&u8::from(*controller)));
+		let controller_message = |(controller, value):(u7, u7)| MidiMessage::Controller { controller, value };
+
+		let mut messages = vec![];
+		if chase.program_changes {
+			if let Some(program) = self.program {
+				messages.extend(bank_select_controllers.into_iter().map(controller_message));
+				messages.push(MidiMessage::ProgramChange { program });
+			}
+		}
+		if chase.controllers {
+			messages.extend(other_controllers.into_iter().map(controller_message));
+		}
+		if chase.pitch_bend {
+			if let Some(bend) = self.pitch_bend {
+				messages.push(MidiMessage::PitchBend { bend });
+			}
+		}
+		if chase.channel_pressure {
+			if let Some(vel) = self.channel_pressure {
+				messages.push(MidiMessage::ChannelAftertouch { vel });
+			}
+		}
+
+		messages
+			.into_iter()
+			.map(|message| MidiEvent { channel, message })
+			.collect()
+	}
+}
@@ -1,6 +1,6 @@
 #[derive(Debug)]
 pub enum Error {
-	/// An incompatible timing value was supplied.
+	/// An incompatible timing value was supplied (eg. zero ticks per beat or per frame).
 	TimingFormat,
 	/// This midi file does not contain any tempo information.
 	NoTempo
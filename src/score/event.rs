@@ -3,11 +3,16 @@ use midly::TrackEventKind;
 use super::midi_event::MidiEvent;
 use super::meta_event::MetaEvent;
 
+/// A single event of a midi score.
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub enum Event {
+	/// A channel message (eg. a note or controller change).
 	Midi(MidiEvent),
+	/// The data of a system exclusive message, without the leading `0xF0` status byte.
 	SysEx(Vec<u8>),
+	/// Arbitrary data, sent to the device as-is (eg. system realtime or common messages).
 	Escape(Vec<u8>),
+	/// Information about the score which isn't sent to the device (eg. tempo, lyrics, markers).
 	Meta(MetaEvent)
 }
 
@@ -0,0 +1,54 @@
+use super::Event;
+
+/// A selection of the kinds of [Event] to be collected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EventKinds {
+	/// Include [`Event::Midi`] events.
+	pub midi: bool,
+	/// Include [`Event::SysEx`] events.
+	pub sysex: bool,
+	/// Include [`Event::Escape`] events.
+	pub escape: bool,
+	/// Include [`Event::Meta`] events.
+	pub meta: bool,
+}
+
+impl Default for EventKinds {
+	fn default() -> EventKinds {
+		EventKinds::all()
+	}
+}
+
+impl EventKinds {
+	/// An [`EventKinds`] which includes every kind of [Event].
+	pub fn all() -> EventKinds {
+		EventKinds {
+			midi: true,
+			sysex: true,
+			escape: true,
+			meta: true
+		}
+	}
+
+	/// An [`EventKinds`] which includes only [`Event::Midi`] events.
+	pub fn midi_only() -> EventKinds {
+		EventKinds {
+			midi: true,
+			sysex: false,
+			escape: false,
+			meta: false
+		}
+	}
+}
+
+impl EventKinds {
+	/// Whether the given [Event] is of a kind included in this selection.
+	pub fn includes(&self, event:&Event) -> bool {
+		match event {
+			Event::Midi(_) => self.midi,
+			Event::SysEx(_) => self.sysex,
+			Event::Escape(_) => self.escape,
+			Event::Meta(_) => self.meta,
+		}
+	}
+}
@@ -0,0 +1,184 @@
+use std::{ops::Range, time::Duration};
+
+use super::{Event, MetaEvent, Score};
+
+/// A single syllable of a score's lyrics.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Syllable {
+	/// The text of the syllable, with any formatting characters removed.
+	pub text: String,
+	/// The index of the track the syllable was found in.
+	pub track: usize,
+	/// The position of the syllable in ticks.
+	pub tick: usize,
+	/// The position of the syllable in time (at normal speed).
+	pub time: Duration,
+}
+
+/// A line of lyrics, as a range of indices into [`Lyrics::get_syllables`].
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Line {
+	/// The indices of the syllables that make up this line.
+	pub syllables: Range<usize>,
+	/// Whether this line begins a new paragraph (or "page") of lyrics.
+	pub new_paragraph: bool,
+}
+
+/// The lyrics of a score, split into syllables and lines.
+///
+/// Lyrics are taken from the score's lyric meta events, or, if there are none, from its text meta events
+/// (as used by .KAR karaoke files). Where lyrics are spread across multiple tracks, the track with the most
+/// syllables is used.
+///
+/// Line breaks are recognised from carriage returns or line feeds at the beginning or end of a syllable,
+/// along with the .KAR conventions of `/` (new line) and `\` (new paragraph) at the beginning of a syllable.
+/// Text events beginning with `@` (.KAR header information) are ignored.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Lyrics {
+	syllables: Vec<Syllable>,
+	lines: Vec<Line>,
+}
+
+impl Lyrics {
+	pub(super) fn new(score:&Score) -> Lyrics {
+		//collect raw lyric text from each track, preferring lyric events over text events
+			let collect = |select:fn(&MetaEvent) -> Option<&Vec<u8>>| -> Vec<Vec<(usize, String)>> {
+				score.tracks
+					.iter()
+					.map(|track| {
+						track
+							.iter_events()
+							.filter_map(|(tick, event)| {
+								if let Event::Meta(meta_event) = event {
+									select(meta_event).map(|data| (tick, String::from_utf8_lossy(data).into_owned()))
+								} else {
+									None
+								}
+							})
+							.collect()
+					})
+					.collect()
+			};
+
+			let mut raw_syllables_per_track = collect(|meta_event| if let MetaEvent::Lyric(data) = meta_event { Some(data) } else { None });
+			if raw_syllables_per_track.iter().all(Vec::is_empty) {
+				raw_syllables_per_track = collect(|meta_event| {
+					match meta_event {
+						MetaEvent::Text(data) if !data.starts_with(b"@") => Some(data),
+						_ => None
+					}
+				});
+			}
+
+			let Some((track, raw_syllables)) = raw_syllables_per_track
+				.into_iter()
+				.enumerate()
+				.filter(|(_, raw_syllables)| !raw_syllables.is_empty())
+				.max_by_key(|(track, raw_syllables)| (raw_syllables.len(), std::cmp::Reverse(*track)))
+			else {
+				return Lyrics::default();
+			};
+
+		//split into syllables and lines
+			let mut syllables = vec![];
+			let mut lines = vec![];
+			// the index of the first syllable of the current line, and whether that line begins a new paragraph
+			let mut current_line:Option<(usize, bool)> = None;
+			let mut new_line = false;
+			let mut new_paragraph = false;
+
+			for (tick, raw_text) in raw_syllables {
+				let mut text = raw_text.as_str();
+
+				//leading formatting
+					if let Some(remainder) = text.strip_prefix('\\') {
+						new_paragraph = true;
+						text = remainder;
+					} else if let Some(remainder) = text.strip_prefix('/') {
+						new_line = true;
+						text = remainder;
+					}
+					if text.starts_with(['\r', '\n']) {
+						new_line = true;
+					}
+
+				//trailing formatting
+					let ends_line = text.ends_with(['\r', '\n']);
+					let text:String = text.chars().filter(|character| !character.is_control()).collect();
+
+				//register syllable
+					if !text.is_empty() {
+						if current_line.is_none() || new_line || new_paragraph {
+							if let Some((start, is_new_paragraph)) = current_line {
+								lines.push(Line { syllables: start..syllables.len(), new_paragraph: is_new_paragraph });
+							}
+							current_line = Some((syllables.len(), new_paragraph || lines.is_empty()));
+						}
+
+						syllables.push(
+							Syllable {
+								text,
+								track,
+								tick,
+								time: score.calculate_duration_until(1.0, tick)
+							}
+						);
+
+						new_line = false;
+						new_paragraph = false;
+					}
+
+					if ends_line {
+						new_line = true;
+					}
+			}
+
+			if let Some((start, is_new_paragraph)) = current_line {
+				lines.push(Line { syllables: start..syllables.len(), new_paragraph: is_new_paragraph });
+			}
+
+		Lyrics {
+			syllables,
+			lines
+		}
+	}
+}
+
+impl Lyrics {
+	pub fn is_empty(&self) -> bool {
+		self.syllables.is_empty()
+	}
+	pub fn get_syllables(&self) -> &[Syllable] {
+		&self.syllables
+	}
+	pub fn get_lines(&self) -> &[Line] {
+		&self.lines
+	}
+
+	/// Get the text of the line with the given index, with its syllables joined together.
+	pub fn get_line_text(&self, line_index:usize) -> Option<String> {
+		self.lines.get(line_index).map(|line| 
+			self.syllables[line.syllables.clone()]
+				.iter()
+				.map(|syllable| syllable.text.as_str())
+				.collect()
+		)
+	}
+
+	/// Get the index of the line containing the syllable with the given index.
+	pub fn get_line_index_of_syllable(&self, syllable_index:usize) -> Option<usize> {
+		self.lines.iter().position(|line| line.syllables.contains(&syllable_index))
+	}
+
+	/// Get the indices of the syllables which occur at exactly the given tick.
+	pub fn get_syllable_indices_at(&self, tick:usize) -> Range<usize> {
+		let start = self.syllables.partition_point(|syllable| syllable.tick < tick);
+		let end = self.syllables.partition_point(|syllable| syllable.tick <= tick);
+		start..end
+	}
+
+	/// Get the index of the latest syllable at or before the given tick.
+	pub fn get_syllable_index_at_or_before(&self, tick:usize) -> Option<usize> {
+		self.syllables.partition_point(|syllable| syllable.tick <= tick).checked_sub(1)
+	}
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use super::{Event, MetaEvent, Score};
+
+/// The kind of meta event a [Marker] was taken from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MarkerKind {
+	/// A [`MetaEvent::Marker`], usually naming a section of the score (eg. "Chorus 2").
+	Marker,
+	/// A [`MetaEvent::CuePoint`], usually describing something that happens at that moment.
+	CuePoint,
+}
+
+/// A named position in a score, taken from its marker and cue point meta events.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Marker {
+	/// The text of the marker.
+	pub name: String,
+	/// The kind of meta event the marker was taken from.
+	pub kind: MarkerKind,
+	/// The index of the track the marker was found in.
+	pub track: usize,
+	/// The position of the marker in ticks.
+	pub tick: usize,
+	/// The position of the marker in time (at normal speed).
+	pub time: Duration,
+}
+
+impl Marker {
+	/// Collect every marker and cue point of the score, ordered by position.
+	pub(super) fn collect(score:&Score) -> Vec<Marker> {
+		let mut markers:Vec<Marker> = score.tracks
+			.iter()
+			.enumerate()
+			.flat_map(|(track, events)| {
+				events
+					.iter_events()
+					.filter_map(move |(tick, event)| {
+						let (kind, data) = match event {
+							Event::Meta(MetaEvent::Marker(data)) => (MarkerKind::Marker, data),
+							Event::Meta(MetaEvent::CuePoint(data)) => (MarkerKind::CuePoint, data),
+							_ => return None
+						};
+
+						Some(
+							Marker {
+								name: String::from_utf8_lossy(data).trim().to_string(),
+								kind,
+								track,
+								tick,
+								time: score.calculate_duration_until(1.0, tick)
+							}
+						)
+					})
+			})
+			.collect();
+		markers.sort_by_key(|marker| (marker.tick, marker.track));
+
+		markers
+	}
+}
@@ -4,6 +4,7 @@ use midly::{
 	SmpteTime
 };
 
+/// An owned version of [`midly::MetaMessage`].
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub enum MetaEvent {
 	/// For `Format::Sequential` MIDI file types, `TrackNumber` can be empty, and defaults to
@@ -1,14 +1,19 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, ops::Range, time::Duration};
 
-use midly::{num::u24, Format, Smf, Timing, TrackEvent};
+use midly::{num::{u24, u4}, Format, Smf, Timing, TrackEvent};
 
 use super::Timer;
 
 mod midi_event;
 mod meta_event;
 mod event;
+mod event_kinds;
 mod simultaneous_events;
 mod track;
+mod chase;
+mod lyrics;
+mod marker;
+mod bbt;
 mod error;
 
 #[cfg(test)]
@@ -17,8 +22,15 @@ mod tests;
 pub use midi_event::MidiEvent;
 pub use meta_event::MetaEvent;
 pub use event::Event;
+pub use event_kinds::EventKinds;
 pub use simultaneous_events::SimultaneousEvents;
 use track::Track;
+pub use chase::Chase;
+use chase::ChannelState;
+pub use lyrics::{Line, Lyrics, Syllable};
+pub use marker::{Marker, MarkerKind};
+pub use bbt::Bbt;
+use bbt::Meter;
 pub use error::Error;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -27,7 +39,10 @@ pub struct Score {
 	tracks: Vec<Track>,
 
 	microseconds_per_beat_changes: Vec<(usize, u24)>,
-	ticks_until_next_events_codex: Vec<usize>
+	meters: Vec<Meter>,
+
+	lyrics: Lyrics,
+	markers: Vec<Marker>
 }
 
 impl Score {
@@ -50,8 +65,8 @@ impl Score {
 	}
 
 	pub fn new(standard_midi_file:&Smf) -> Result<Score, Error> {
-		//check timecode
-			if let Timing::Timecode(_, _) = standard_midi_file.header.timing {
+		//check timing
+			if Timer::new(standard_midi_file.header.timing, Duration::ZERO).is_err() {
 				return Err(Error::TimingFormat);
 			}
 
@@ -62,39 +77,51 @@ impl Score {
 			};
 
 		//microseconds_per_beat_changes
-			let mut microseconds_per_beat_changes:Vec<(usize, u24)> = tracks
-				.iter()
-				.flat_map(|track| track.get_all_tempos().to_vec())
-				.collect();
-			microseconds_per_beat_changes.sort_by_key(|tempo| tempo.0);
-			if microseconds_per_beat_changes.is_empty() {
-				return Err(Error::NoTempo);
-			}
+			// (SMPTE timecode scores have a fixed tick duration, so any tempo messages are ignored)
+			let microseconds_per_beat_changes:Vec<(usize, u24)> = if let Timing::Timecode(_, _) = standard_midi_file.header.timing {
+				vec![]
+			} else {
+				let mut microseconds_per_beat_changes:Vec<(usize, u24)> = tracks
+					.iter()
+					.flat_map(|track| track.get_all_tempos().to_vec())
+					.collect();
+				microseconds_per_beat_changes.sort_by_key(|tempo| tempo.0);
+				if microseconds_per_beat_changes.is_empty() {
+					return Err(Error::NoTempo);
+				}
+				microseconds_per_beat_changes
+			};
 
-		//ticks_until_next_events_codex
-			let mut ticks_until_next_events_codex = tracks[0].calculate_ticks_until_next_event_codex();
-			tracks.iter().skip(1).for_each(|track| {
-				track.calculate_ticks_until_next_event_codex().into_iter().enumerate().for_each(|(index, distance)| {
-					if ticks_until_next_events_codex.len() <= index {
-						ticks_until_next_events_codex.push(distance);
-					} else if ticks_until_next_events_codex[index] > distance {
-						ticks_until_next_events_codex[index] = distance;
-					}
-				});
-			});
-		
-		Ok(
-			Score {
-				timing: standard_midi_file.header.timing,
-				tracks,
-				microseconds_per_beat_changes,
-				ticks_until_next_events_codex
-			}
-		)
+		//meters
+			// (SMPTE timecode scores have no beats, so cannot be divided into bars)
+			let meters = if let Timing::Metrical(ticks_per_quarter_note) = standard_midi_file.header.timing {
+				Meter::collect(&tracks, u16::from(ticks_per_quarter_note))
+			} else {
+				vec![]
+			};
+
+		let mut score = Score {
+			timing: standard_midi_file.header.timing,
+			tracks,
+			microseconds_per_beat_changes,
+			meters,
+
+			lyrics: Lyrics::default(),
+			markers: vec![]
+		};
+
+		//lyrics and markers
+			score.lyrics = Lyrics::new(&score);
+			score.markers = Marker::collect(&score);
+
+		Ok(score)
 	}
 }
 
 impl Score {
+	pub fn get_timing(&self) -> Timing {
+		self.timing
+	}
 	pub fn get_track_count(&self) -> usize {
 		self.tracks.len()
 	}
@@ -105,6 +132,18 @@ impl Score {
 			.max()
 			.unwrap_or(0)
 	}
+	pub fn get_lyrics(&self) -> &Lyrics {
+		&self.lyrics
+	}
+	pub fn get_markers(&self) -> &[Marker] {
+		&self.markers
+	}
+	/// Get the indices of the markers which occur at exactly the given tick.
+	pub fn get_marker_indices_at(&self, index:usize) -> Range<usize> {
+		let start = self.markers.partition_point(|marker| marker.tick < index);
+		let end = self.markers.partition_point(|marker| marker.tick <= index);
+		start..end
+	}
 }
 
 impl Score {
@@ -160,31 +199,71 @@ impl Score {
 		counter
 	}
 
+	/// Calculate the tick position reached after playing for the given duration at the given speed
+	/// (the inverse of [`Score::calculate_duration_until`]), or the length of the score if the duration reaches its end.
+	pub fn calculate_ticks_at_duration(&self, speed:f32, duration:Duration) -> usize {
+		//protection
+			if speed == 0.0 {
+				return 0;
+			}
+			let length = self.len();
+			if duration >= self.calculate_duration(speed) {
+				return length;
+			}
+
+		//create new timer
+			let mut working_timer = Timer::new(self.timing, Duration::ZERO).expect("we ensured that the timing format was compatible in the \"new\" method");
+			working_timer.set_speed(speed);
+
+		//find the tempo in effect at the duration
+			let mut counter = Duration::default();
+			let mut last_index = 0;
+			for (index, microseconds_per_beat) in &self.microseconds_per_beat_changes {
+				let segment = working_timer.calculate_duration_of_ticks(*index - last_index);
+				if counter + segment > duration {
+					break;
+				}
+
+				counter += segment;
+				last_index = *index;
+				working_timer.change_tempo(u32::from(*microseconds_per_beat));
+			}
+
+		//estimate, then correct for rounding
+			let tick_duration = working_timer.calculate_duration_of_ticks(1);
+			if tick_duration.is_zero() {
+				return last_index;
+			}
+			let mut index = usize::try_from((duration - counter).as_nanos() / tick_duration.as_nanos())
+				.ok()
+				.and_then(|ticks| last_index.checked_add(ticks))
+				.map_or(length, |index| index.min(length));
+			while index > last_index && self.calculate_duration_until(speed, index) > duration {
+				index -= 1;
+			}
+			while index < length && self.calculate_duration_until(speed, index + 1) <= duration {
+				index += 1;
+			}
+
+		index
+	}
+
 	pub fn gather_all_events_for_index(&self, index:usize) -> Option<Vec<(usize, &SimultaneousEvents)>> {
-		let potential_simultaneous_events_for_index_with_track_index:Vec<(usize, &Option<SimultaneousEvents>)> = self.tracks
-			.iter()
-			.enumerate()
-			.filter_map(|(track_index, track)| 
-				track
-					.get_events(index)
-					.map(|potential_simultaneous_events| (track_index, potential_simultaneous_events))
-			)
-			.collect();
-
-		if potential_simultaneous_events_for_index_with_track_index.is_empty() {
-			None
-		} else {
-			Some(
-				potential_simultaneous_events_for_index_with_track_index
-					.into_iter()
-					.filter_map(|(track_index, potential_simultaneous_events)| 
-						potential_simultaneous_events
-							.as_ref()
-							.map(|simultaneous_events| (track_index, simultaneous_events))
-					)
-					.collect()
-			)
+		if index >= self.len() {
+			return None;
 		}
+
+		Some(
+			self.tracks
+				.iter()
+				.enumerate()
+				.filter_map(|(track_index, track)| 
+					track
+						.get_events(index)
+						.map(|simultaneous_events| (track_index, simultaneous_events))
+				)
+				.collect()
+		)
 	}
 }
 
@@ -197,19 +276,56 @@ impl Score {
 		} else if self.microseconds_per_beat_changes.len() == 1 {
 			self.microseconds_per_beat_changes.first().map(|(_, tempo)| *tempo)
 		} else {
-			self.microseconds_per_beat_changes
-				.iter()
-				.filter(|(tempo_index, _)| tempo_index <= &index)
-				.last()
-				.map(|(_, tempo)| *tempo)
+			let change_count = self.microseconds_per_beat_changes.partition_point(|(tempo_index, _)| tempo_index <= &index);
+			change_count.checked_sub(1).map(|change_index| self.microseconds_per_beat_changes[change_index].1)
 		}
 	}
 
 	pub fn calculate_ticks_until_next_events_from_index(&self, index:usize) -> Option<usize> {
-		if self.ticks_until_next_events_codex.len() <= index + 1 {
-			return None;
+		self.tracks
+			.iter()
+			.filter_map(|track| track.get_ticks_until_next_events_from(index))
+			.min()
+	}
+}
+
+impl Score {
+	/// Gather the events which recreate the channel state (as selected by `chase`) that each track has built up
+	/// before the given index.
+	pub fn gather_chase_events_until(&self, index:usize, chase:Chase) -> Vec<(usize, MidiEvent)> {
+		if chase.is_none() {
+			return vec![];
 		}
 
-		Some(self.ticks_until_next_events_codex[index])
+		self.tracks
+			.iter()
+			.enumerate()
+			.flat_map(|(track_index, track)| {
+				let mut channel_states:BTreeMap<u4, ChannelState> = BTreeMap::new();
+				for event in track.get_events_before(index) {
+					if let Event::Midi(midi_event) = event {
+						channel_states.entry(midi_event.channel).or_default().register(&midi_event.message);
+					}
+				}
+
+				channel_states
+					.into_iter()
+					.flat_map(move |(channel, channel_state)| channel_state.into_events(channel, chase))
+					.map(move |midi_event| (track_index, midi_event))
+			})
+			.collect()
+	}
+}
+
+impl Score {
+	/// Convert a tick position to a [Bbt] position, returning `None` for SMPTE timecode scores.
+	pub fn ticks_to_bbt(&self, index:usize) -> Option<Bbt> {
+		Meter::ticks_to_bbt(&self.meters, index)
+	}
+
+	/// Convert a [Bbt] position to a tick position, returning `None` for SMPTE timecode scores, or if the beat or tick
+	/// lies outside of its bar.
+	pub fn bbt_to_ticks(&self, bbt:Bbt) -> Option<usize> {
+		Meter::bbt_to_ticks(&self.meters, bbt)
 	}
-}
\ No newline at end of file
+}
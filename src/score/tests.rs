@@ -1,3 +1,5 @@
+#![allow(clippy::identity_op)]
+
 mod constant_tempo {
 	use std::time::Duration;
 
@@ -123,6 +125,29 @@ mod constant_tempo {
 			Some(960)
 		);
 	}
+
+	#[test]
+	pub fn calculate_ticks_until_next_events_from_index_after_last_events() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(
+			score.calculate_ticks_until_next_events_from_index(score.len() - 1),
+			None
+		);
+	}
+
+	#[test]
+	pub fn gather_all_events_for_index() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert!(!score.gather_all_events_for_index(0).unwrap().is_empty());
+		assert_eq!(score.gather_all_events_for_index(1).unwrap(), []);
+		assert!(!score.gather_all_events_for_index(960).unwrap().is_empty());
+		assert!(!score.gather_all_events_for_index(score.len() - 1).unwrap().is_empty());
+		assert_eq!(score.gather_all_events_for_index(score.len()), None);
+	}
 }
 
 mod changing_tempo {
@@ -228,4 +253,356 @@ mod changing_tempo {
 			Duration::from_secs_f64(3.998331861)
 		);
 	}
-}
\ No newline at end of file
+
+	#[test]
+	pub fn calculate_ticks_at_duration() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.calculate_ticks_at_duration(1.0, Duration::from_secs_f64(3.998331861)), 3841);
+		assert_eq!(score.calculate_ticks_at_duration(2.0, score.calculate_duration_until(2.0, 9000)), 9000);
+		assert!(score.calculate_ticks_at_duration(1.0, score.calculate_duration(1.0)) >= score.len());
+	}
+
+	#[test]
+	pub fn calculate_ticks_beyond_the_end() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.calculate_ticks_at_duration(1.0, score.calculate_duration(1.0) + Duration::from_secs(1)), score.len());
+		assert_eq!(score.calculate_ticks_at_duration(1.0, Duration::from_secs(100_000_000)), score.len());
+		assert_eq!(score.calculate_ticks_at_duration(0.5, Duration::MAX), score.len());
+	}
+}
+mod timecode {
+	use std::time::Duration;
+
+	use super::super::Score;
+
+	// 25 frames per second, 40 ticks per frame (one tick per millisecond), with tempo messages that should be ignored
+	static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/timecode.mid");
+
+	#[test]
+	pub fn len() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+		assert_eq!(score.len(), 3001);
+	}
+
+	#[test]
+	pub fn get_microseconds_per_beat_changes() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.microseconds_per_beat_changes, []);
+		assert_eq!(score.get_microseconds_per_beat_at(0), None);
+	}
+
+	#[test]
+	pub fn calculate_duration() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.calculate_duration(1.0), Duration::from_secs_f64(3.000999928));
+		assert_eq!(score.calculate_duration(2.0), Duration::from_secs_f64(1.500499964));
+	}
+
+	#[test]
+	pub fn calculate_duration_until() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.calculate_duration_until(1.0, 2000), Duration::from_millis(2000));
+	}
+
+	#[test]
+	pub fn zero_ticks_per_frame() {
+		let mut standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		standard_midi_file.header.timing = midly::Timing::Timecode(midly::Fps::Fps25, 0);
+
+		assert!(Score::new(&standard_midi_file).is_err());
+	}
+}
+
+mod channel_state {
+	use midly::{num::{u14, u4, u7}, MidiMessage, PitchBend};
+
+	use super::super::{Chase, MidiEvent, Score};
+
+	static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/channel_state.mid");
+
+	fn event(channel:u8, message:MidiMessage) -> MidiEvent {
+		MidiEvent { channel: u4::new(channel), message }
+	}
+	fn controller(channel:u8, controller:u8, value:u8) -> MidiEvent {
+		event(channel, MidiMessage::Controller { controller: u7::new(controller), value: u7::new(value) })
+	}
+	fn program_change(channel:u8, program:u8) -> MidiEvent {
+		event(channel, MidiMessage::ProgramChange { program: u7::new(program) })
+	}
+
+	#[test]
+	pub fn nothing_before_start() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.gather_chase_events_until(0, Chase::all()), []);
+	}
+
+	#[test]
+	pub fn nothing_chased() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.gather_chase_events_until(500, Chase::none()), []);
+	}
+
+	#[test]
+	pub fn all_chased() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(
+			score.gather_chase_events_until(193, Chase::all()),
+			[
+				(1, controller(0, 0, 1)),
+				(1, controller(0, 32, 0)),
+				(1, program_change(0, 5)),
+				(1, controller(0, 7, 80)),
+				(1, controller(0, 64, 127)),
+				(1, event(0, MidiMessage::PitchBend { bend: PitchBend(u14::new(0x3000)) })),
+				(2, program_change(9, 0)),
+			]
+		);
+	}
+
+	#[test]
+	pub fn later_values_replace_earlier_ones() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(
+			score.gather_chase_events_until(500, Chase::all()),
+			[
+				(1, controller(0, 0, 1)),
+				(1, controller(0, 32, 0)),
+				(1, program_change(0, 10)),
+				(1, controller(0, 7, 80)),
+				(1, controller(0, 64, 0)),
+				(1, event(0, MidiMessage::PitchBend { bend: PitchBend(u14::new(0x3000)) })),
+				(1, event(0, MidiMessage::ChannelAftertouch { vel: u7::new(50) })),
+				(2, program_change(9, 0)),
+				(2, controller(9, 10, 20)),
+			]
+		);
+	}
+
+	#[test]
+	pub fn only_program_changes_chased() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(
+			score.gather_chase_events_until(500, Chase { program_changes: true, ..Chase::none() }),
+			[
+				(1, controller(0, 0, 1)),
+				(1, controller(0, 32, 0)),
+				(1, program_change(0, 10)),
+				(2, program_change(9, 0)),
+			]
+		);
+	}
+}
+
+mod lyrics {
+	use std::time::Duration;
+
+	use super::super::Score;
+
+	static LYRIC_MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/lyrics.mid");
+	static KARAOKE_MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/karaoke.kar");
+
+	#[test]
+	pub fn no_lyrics() {
+		let standard_midi_file = midly::Smf::parse(include_bytes!("../../test_midi_files/constant_tempo.mid")).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert!(score.get_lyrics().is_empty());
+		assert_eq!(score.get_lyrics().get_lines(), []);
+	}
+
+	#[test]
+	pub fn syllables() {
+		let standard_midi_file = midly::Smf::parse(LYRIC_MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+		let syllables = score.get_lyrics().get_syllables();
+
+		assert_eq!(
+			syllables.iter().map(|syllable| (syllable.text.as_str(), syllable.track, syllable.tick)).collect::<Vec<_>>(),
+			[
+				("Hel", 1, 0),
+				("lo ", 1, 96),
+				("world", 1, 192),
+				("Sec", 1, 288),
+				("ond", 1, 384),
+			]
+		);
+		assert_eq!(syllables[0].time, Duration::ZERO);
+		assert_eq!(syllables[2].time, Duration::from_nanos(999_935_985));
+	}
+
+	#[test]
+	pub fn lines() {
+		let standard_midi_file = midly::Smf::parse(LYRIC_MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+		let lyrics = score.get_lyrics();
+
+		assert_eq!(lyrics.get_lines().len(), 2);
+		assert_eq!(lyrics.get_line_text(0), Some(String::from("Hello world")));
+		assert_eq!(lyrics.get_line_text(1), Some(String::from("Second")));
+		assert_eq!(lyrics.get_line_text(2), None);
+		assert_eq!(lyrics.get_line_index_of_syllable(2), Some(0));
+		assert_eq!(lyrics.get_line_index_of_syllable(3), Some(1));
+	}
+
+	#[test]
+	pub fn syllable_lookup() {
+		let standard_midi_file = midly::Smf::parse(LYRIC_MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+		let lyrics = score.get_lyrics();
+
+		assert_eq!(lyrics.get_syllable_indices_at(96), 1..2);
+		assert_eq!(lyrics.get_syllable_indices_at(97), 2..2);
+		assert_eq!(lyrics.get_syllable_index_at_or_before(0), Some(0));
+		assert_eq!(lyrics.get_syllable_index_at_or_before(200), Some(2));
+	}
+
+	#[test]
+	pub fn karaoke_conventions() {
+		let standard_midi_file = midly::Smf::parse(KARAOKE_MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+		let lyrics = score.get_lyrics();
+
+		assert_eq!(
+			lyrics.get_lines().iter().map(|line| (line.syllables.clone(), line.new_paragraph)).collect::<Vec<_>>(),
+			[
+				(0..2, true),
+				(2..4, false),
+				(4..5, true),
+			]
+		);
+		assert_eq!(lyrics.get_line_text(0), Some(String::from("Twinkle ")));
+		assert_eq!(lyrics.get_line_text(1), Some(String::from("Little")));
+		assert_eq!(lyrics.get_line_text(2), Some(String::from("Star")));
+	}
+}
+
+mod markers {
+	use super::super::{MarkerKind, Score};
+
+	static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/markers.mid");
+
+	#[test]
+	pub fn get_markers() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(
+			score.get_markers().iter().map(|marker| (marker.name.as_str(), marker.kind, marker.track, marker.tick)).collect::<Vec<_>>(),
+			[
+				("Intro", MarkerKind::Marker, 0, 0),
+				("Verse", MarkerKind::Marker, 0, 192),
+				("Lights", MarkerKind::CuePoint, 1, 192),
+				("Chorus 2", MarkerKind::Marker, 0, 384),
+			]
+		);
+		assert_eq!(score.get_markers()[3].time, score.calculate_duration_until(1.0, 384));
+	}
+
+	#[test]
+	pub fn get_marker_indices_at() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.get_marker_indices_at(0), 0..1);
+		assert_eq!(score.get_marker_indices_at(192), 1..3);
+		assert_eq!(score.get_marker_indices_at(193), 3..3);
+	}
+}
+
+mod meters {
+	use std::time::Duration;
+
+	use super::super::{Bbt, Score};
+
+	// 4/4 from bar 1, 3/4 (and double tempo) from bar 3, 6/8 from bar 5 and 2/4 from halfway through bar 5 (beginning bar 6)
+	static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/meters.mid");
+
+	#[test]
+	pub fn ticks_to_bbt() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.ticks_to_bbt(0), Some(Bbt::new(1, 1, 0)));
+		assert_eq!(score.ticks_to_bbt(96), Some(Bbt::new(1, 2, 0)));
+		assert_eq!(score.ticks_to_bbt(100), Some(Bbt::new(1, 2, 4)));
+		assert_eq!(score.ticks_to_bbt(384), Some(Bbt::new(2, 1, 0)));
+		assert_eq!(score.ticks_to_bbt(768), Some(Bbt::new(3, 1, 0)));
+		assert_eq!(score.ticks_to_bbt(874), Some(Bbt::new(3, 2, 10)));
+		assert_eq!(score.ticks_to_bbt(1344), Some(Bbt::new(5, 1, 0)));
+		assert_eq!(score.ticks_to_bbt(1392), Some(Bbt::new(5, 2, 0)));
+		assert_eq!(score.ticks_to_bbt(1487), Some(Bbt::new(5, 3, 47)));
+		assert_eq!(score.ticks_to_bbt(1488), Some(Bbt::new(6, 1, 0)));
+		assert_eq!(score.ticks_to_bbt(1680), Some(Bbt::new(7, 1, 0)));
+	}
+
+	#[test]
+	pub fn bbt_to_ticks() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.bbt_to_ticks(Bbt::new(1, 1, 0)), Some(0));
+		assert_eq!(score.bbt_to_ticks(Bbt::new(3, 2, 10)), Some(874));
+		assert_eq!(score.bbt_to_ticks(Bbt::new(5, 3, 47)), Some(1487));
+		assert_eq!(score.bbt_to_ticks(Bbt::new(6, 1, 0)), Some(1488));
+		assert_eq!(score.bbt_to_ticks(Bbt::new(7, 2, 0)), Some(1776));
+	}
+
+	#[test]
+	pub fn bbt_to_ticks_invalid() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.bbt_to_ticks(Bbt::new(0, 1, 0)), None);
+		assert_eq!(score.bbt_to_ticks(Bbt::new(1, 0, 0)), None);
+		assert_eq!(score.bbt_to_ticks(Bbt::new(1, 5, 0)), None);
+		assert_eq!(score.bbt_to_ticks(Bbt::new(1, 1, 96)), None);
+		// bar 5 is cut short by the change to 2/4
+		assert_eq!(score.bbt_to_ticks(Bbt::new(5, 4, 0)), None);
+	}
+
+	#[test]
+	pub fn no_time_signature() {
+		let standard_midi_file = midly::Smf::parse(include_bytes!("../../test_midi_files/timecode.mid")).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		assert_eq!(score.ticks_to_bbt(0), None);
+		assert_eq!(score.bbt_to_ticks(Bbt::new(1, 1, 0)), None);
+	}
+
+	#[test]
+	pub fn calculate_ticks_at_duration() {
+		let standard_midi_file = midly::Smf::parse(MID_FILE_DATA).unwrap();
+		let score = Score::new(&standard_midi_file).ok().unwrap();
+
+		for speed in [1.0, 2.0, 0.5] {
+			for ticks in [0, 1, 95, 96, 767, 768, 769, 1000, 1871] {
+				assert_eq!(score.calculate_ticks_at_duration(speed, score.calculate_duration_until(speed, ticks)), ticks);
+			}
+		}
+
+		assert_eq!(score.calculate_ticks_at_duration(1.0, Duration::ZERO), 0);
+		assert_eq!(score.calculate_ticks_at_duration(1.0, Duration::from_millis(500)), 96);
+		assert_eq!(score.calculate_ticks_at_duration(0.0, Duration::from_secs(1)), 0);
+	}
+}
@@ -7,116 +7,105 @@ use super::simultaneous_events::SimultaneousEvents;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Track {
-	// eg. [[Event], none, none, none, [Event, Event], none, none, [Event]]
-	potential_simultaneous_events_sequence: Vec<Option<SimultaneousEvents>>,
+	// eg. [(0, [Event]), (4, [Event, Event]), (7, [Event])]
+	simultaneous_events_sequence: Vec<(usize, SimultaneousEvents)>,
+	length: usize,
 	all_tempos: Vec<(usize, u24)>
 }
 
 impl From<&Vec<TrackEvent<'_>>> for Track {
 	fn from(track_events:&Vec<TrackEvent<'_>>) -> Track {
-		//create vector
-			let total_ticks = track_events
-				.iter()
-				.map(|track_event| {
-					//correct "EndOfTrack being too far out" situation
-					if TrackEventKind::Meta(MetaMessage::EndOfTrack) == track_event.kind {
-						0
-					} else {
-						u32::from(track_event.delta) as usize
-					}
-				})
-				.sum::<usize>()
-				+ 1;
-			let mut potential_simultaneous_events_sequence:Vec<Option<SimultaneousEvents>> = vec![None; total_ticks];
-			
-		//populate vector
+		//populate sequence
+			let mut simultaneous_events_sequence:Vec<(usize, SimultaneousEvents)> = vec![];
 			let mut position = 0;
 			for track_event in track_events {
+				//correct "EndOfTrack being too far out" situation
 				if TrackEventKind::Meta(MetaMessage::EndOfTrack) == track_event.kind {
 					continue;
 				}
 
 				position += u32::from(track_event.delta) as usize;
 				if let Ok(event) = Event::try_from(track_event.kind) {
-					if let Some(simultaneous_events) = &mut potential_simultaneous_events_sequence[position] {
-						simultaneous_events.push(event);
-					} else {
-						potential_simultaneous_events_sequence[position] = Some(SimultaneousEvents::new(vec![event]));
+					match simultaneous_events_sequence.last_mut() {
+						Some((index, simultaneous_events)) if *index == position => simultaneous_events.push(event),
+						_ => simultaneous_events_sequence.push((position, SimultaneousEvents::new(vec![event]))),
 					}
 				}
 			}
-		
+
 		//all_tempos
-			let all_tempos = potential_simultaneous_events_sequence
+			let all_tempos = simultaneous_events_sequence
 				.iter()
-				.enumerate()
-				.filter_map(|(index, potential_simultaneous_event)| 
-					potential_simultaneous_event
-						.as_ref()
-						.map(|simultaneous_event|
-							simultaneous_event.events
-								.iter()
-								.filter_map(|event|
-									if let Event::Meta(MetaEvent::Tempo(microseconds_per_beat)) = event {
-										Some((index, *microseconds_per_beat))
-									} else {
-										None
-									}
-								)
-								.collect::<Vec<(usize, u24)>>()
-					)
+				.flat_map(|(index, simultaneous_events)| 
+					simultaneous_events.events
+						.iter()
+						.filter_map(|event|
+							if let Event::Meta(MetaEvent::Tempo(microseconds_per_beat)) = event {
+								Some((*index, *microseconds_per_beat))
+							} else {
+								None
+							}
+						)
 				)
-				.flatten()
 				.collect();
 
 		Track {
-			potential_simultaneous_events_sequence,
+			simultaneous_events_sequence,
+			length: position + 1,
 			all_tempos
 		}
 	}
 }
 
 impl Track {
+	/// Append the other track to the end of this one.
 	pub fn append(&mut self, other:&mut Track) {
-		self.potential_simultaneous_events_sequence.append(&mut other.potential_simultaneous_events_sequence);	
-	}
-}
-
-impl Track {
-	pub(super) fn calculate_ticks_until_next_event_codex(&self) -> Vec<usize> {
-		// eg. [[Event], none, none, none, [Event, Event], none, none, [Event]]
-		//   > [4, 3, 2, 1, 3, 2, 1, 0]
-
-		let mut ticks_until_next_events_compressed_codex = vec![];
-		let mut counter = 0;
-		for event in &self.potential_simultaneous_events_sequence {
-			if event.is_some() {
-				ticks_until_next_events_compressed_codex.push(counter);
-				counter = 1;
-			} else {
-				counter += 1;
-			}
-		}
-
-		let mut ticks_until_next_events_codex = vec![];
-		for distance in ticks_until_next_events_compressed_codex {
-			let mut working_distance = distance;
-			while working_distance > 0 {
-				ticks_until_next_events_codex.push(working_distance);
-				working_distance -= 1;
-			}
-		}
-
-		ticks_until_next_events_codex
+		let offset = self.length;
+		self.simultaneous_events_sequence.extend(
+			other.simultaneous_events_sequence
+				.drain(..)
+				.map(|(index, simultaneous_events)| (index + offset, simultaneous_events))
+		);
+		self.all_tempos.extend(
+			other.all_tempos
+				.drain(..)
+				.map(|(index, microseconds_per_beat)| (index + offset, microseconds_per_beat))
+		);
+		self.length += other.length;
 	}
 }
 
 impl Track {
 	pub fn len(&self) -> usize {
-		self.potential_simultaneous_events_sequence.len()
+		self.length
+	}
+	pub fn get_events(&self, index:usize) -> Option<&SimultaneousEvents> {
+		self.simultaneous_events_sequence
+			.binary_search_by_key(&index, |(event_index, _)| *event_index)
+			.ok()
+			.map(|sequence_index| &self.simultaneous_events_sequence[sequence_index].1)
 	}
-	pub fn get_events(&self, index:usize) -> Option<&Option<SimultaneousEvents>> {
-		self.potential_simultaneous_events_sequence.get(index)
+	/// Get the number of ticks from the given index until the track's next events, or `None` if there are no more.
+	pub fn get_ticks_until_next_events_from(&self, index:usize) -> Option<usize> {
+		let sequence_index = self.simultaneous_events_sequence.partition_point(|(event_index, _)| *event_index <= index);
+		self.simultaneous_events_sequence
+			.get(sequence_index)
+			.map(|(event_index, _)| event_index - index)
+	}
+	/// Iterate through every event in the track, in order, along with the index it occurs at.
+	pub fn iter_events(&self) -> impl Iterator<Item = (usize, &Event)> {
+		Track::flatten(&self.simultaneous_events_sequence)
+	}
+	/// Iterate through every event that occurs before the given index, in order.
+	pub fn get_events_before(&self, index:usize) -> impl Iterator<Item = &Event> {
+		let end = self.simultaneous_events_sequence.partition_point(|(event_index, _)| *event_index < index);
+		Track::flatten(&self.simultaneous_events_sequence[..end]).map(|(_, event)| event)
+	}
+
+	fn flatten(simultaneous_events_sequence:&[(usize, SimultaneousEvents)]) -> impl Iterator<Item = (usize, &Event)> {
+		simultaneous_events_sequence
+			.iter()
+			.flat_map(|(index, simultaneous_events)| simultaneous_events.events.iter().map(move |event| (*index, event)))
 	}
 }
 
@@ -124,4 +113,4 @@ impl Track {
 	pub fn get_all_tempos(&self) -> &[(usize, u24)] {
 		&self.all_tempos
 	}
-}
\ No newline at end of file
+}
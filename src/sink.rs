@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+use crate::{messages::ToConsole, notification::Notification, score::{Event, MidiEvent}, timestamp::Timestamp};
+
+/// The error type a [`Sink`] may return.
+pub type SinkError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A destination for the events of a playing score.
+///
+/// A sink is handed to the [`Performer`](crate::Performer) on creation and is then called directly from the engine
+/// thread, at the moment each event is due, so an output port can be driven without any polling.
+pub trait Sink: Send {
+	/// Receive a [`MidiEvent`] from the track with the given index.
+	///
+	/// This is called from the engine thread, so any time spent here delays the events that follow.
+	///
+	/// # Errors
+	/// Returning an error halts the engine. The error is then reported by [`Performer::poll`](crate::Performer::poll)
+	/// as an [`EngineError::Sink`](crate::EngineError::Sink).
+	fn receive(&mut self, track:usize, midi_event:MidiEvent) -> Result<(), SinkError>;
+
+	/// Receive the data of a system exclusive message (without the leading `0xF0` status byte) from the track with the given index.
+	///
+	/// By default, system exclusive messages are ignored.
+	///
+	/// # Errors
+	/// Returning an error halts the engine, as with [`Sink::receive`].
+	fn receive_sysex(&mut self, _track:usize, _data:&[u8]) -> Result<(), SinkError> {
+		Ok(())
+	}
+
+	/// Receive any [Event] of the score from the track with the given index.
+	///
+	/// By default, [`Event::Midi`] events are passed on to [`Sink::receive`], [`Event::SysEx`] events are passed on to
+	/// [`Sink::receive_sysex`] and all other events are ignored.
+	///
+	/// # Errors
+	/// Returning an error halts the engine, as with [`Sink::receive`].
+	fn receive_event(&mut self, track:usize, event:&Event) -> Result<(), SinkError> {
+		match event {
+			Event::Midi(midi_event) => self.receive(track, *midi_event),
+			Event::SysEx(data) => self.receive_sysex(track, data),
+			Event::Escape(_) | Event::Meta(_) => Ok(())
+		}
+	}
+
+	/// Receive any [Event] of the score from the track with the given index, along with the [Timestamp] it was
+	/// scheduled for. This is the method the engine calls for every event it sends.
+	///
+	/// By default, the timestamp is discarded and the event is passed on to [`Sink::receive_event`].
+	///
+	/// # Errors
+	/// Returning an error halts the engine, as with [`Sink::receive`].
+	fn receive_timestamped(&mut self, track:usize, event:&Event, _timestamp:&Timestamp) -> Result<(), SinkError> {
+		self.receive_event(track, event)
+	}
+
+	/// Receive a [Notification] about the progress of playback.
+	///
+	/// By default, notifications are ignored.
+	///
+	/// # Errors
+	/// Returning an error halts the engine, as with [`Sink::receive`].
+	fn notify(&mut self, _notification:&Notification) -> Result<(), SinkError> {
+		Ok(())
+	}
+}
+
+impl<F> Sink for F where F: FnMut(usize, MidiEvent) -> Result<(), SinkError> + Send {
+	fn receive(&mut self, track:usize, midi_event:MidiEvent) -> Result<(), SinkError> {
+		self(track, midi_event)
+	}
+}
+
+/// The built-in [`Sink`], which passes events back to the console to be collected by [`Performer::poll`](crate::Performer::poll).
+///
+/// Events received without a [Timestamp] are stamped with the moment they arrive, at the position of the last event
+/// received with one.
+pub struct ChannelSink {
+	channel_to_console: crossbeam_channel::Sender<ToConsole>,
+	last_timestamp: Option<Timestamp>
+}
+
+impl ChannelSink {
+	pub fn new(channel_to_console:crossbeam_channel::Sender<ToConsole>) -> ChannelSink {
+		ChannelSink {
+			channel_to_console,
+			last_timestamp: None
+		}
+	}
+}
+
+impl Sink for ChannelSink {
+	fn receive(&mut self, track:usize, midi_event:MidiEvent) -> Result<(), SinkError> {
+		self.receive_event(track, &Event::Midi(midi_event))
+	}
+
+	fn receive_event(&mut self, track:usize, event:&Event) -> Result<(), SinkError> {
+		let timestamp = match self.last_timestamp {
+			Some(last_timestamp) => Timestamp { instant: Instant::now(), ..last_timestamp },
+			None => Timestamp { tick: 0, instant: Instant::now(), time: Duration::ZERO },
+		};
+
+		self.channel_to_console.send(ToConsole::Event(track, event.clone(), timestamp))?;
+		Ok(())
+	}
+
+	fn receive_timestamped(&mut self, track:usize, event:&Event, timestamp:&Timestamp) -> Result<(), SinkError> {
+		self.last_timestamp = Some(*timestamp);
+		self.channel_to_console.send(ToConsole::Event(track, event.clone(), *timestamp))?;
+		Ok(())
+	}
+
+	fn notify(&mut self, notification:&Notification) -> Result<(), SinkError> {
+		self.channel_to_console.send(ToConsole::Notification(notification.clone()))?;
+		Ok(())
+	}
+}
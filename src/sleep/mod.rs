@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, TryRecvError};
+
+#[cfg(windows)]
+const LIMIT:Duration = Duration::from_millis(15);
+#[cfg(not(windows))]
+const LIMIT:Duration = Duration::from_millis(3);
+
+/// The reason [`wait`] returned.
+pub enum Wake<T> {
+	/// A message arrived.
+	Message(T),
+	/// The deadline was reached.
+	Deadline,
+	/// The sending side of the channel has gone.
+	Disconnected,
+}
+
+/// Block until a message arrives on the channel or the deadline (if any) is reached.
+///
+/// Blocking on the channel is only as accurate as the operating system's scheduler, so the last stretch before the
+/// deadline is spent spinning (while still watching the channel) instead.
+pub fn wait<T>(channel:&Receiver<T>, deadline:Option<Instant>) -> Wake<T> {
+	let Some(deadline) = deadline else {
+		return match channel.recv() {
+			Ok(message) => Wake::Message(message),
+			Err(_) => Wake::Disconnected,
+		};
+	};
+
+	if let Some(blocking_deadline) = deadline.checked_sub(LIMIT) {
+		match channel.recv_deadline(blocking_deadline) {
+			Ok(message) => return Wake::Message(message),
+			Err(RecvTimeoutError::Disconnected) => return Wake::Disconnected,
+			Err(RecvTimeoutError::Timeout) => {},
+		}
+	}
+
+	spin_lock(channel, deadline)
+}
+
+#[inline]
+fn spin_lock<T>(channel:&Receiver<T>, deadline:Instant) -> Wake<T> {
+	while Instant::now() < deadline {
+		match channel.try_recv() {
+			Ok(message) => return Wake::Message(message),
+			Err(TryRecvError::Disconnected) => return Wake::Disconnected,
+			Err(TryRecvError::Empty) => std::hint::spin_loop(),
+		}
+	}
+
+	Wake::Deadline
+}
+
+#[cfg(test)]
+mod tests;
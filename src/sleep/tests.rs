@@ -0,0 +1,43 @@
+use std::{thread, time::{Duration, Instant}};
+
+use super::{wait, Wake, LIMIT};
+
+#[test]
+pub fn message_wakes_early() {
+	let (sender, receiver) = crossbeam_channel::unbounded();
+	let start = Instant::now();
+	let deadline = start + Duration::from_millis(500);
+
+	let sending_thread = thread::spawn(move || {
+		thread::sleep(Duration::from_millis(20));
+		sender.send(1).unwrap();
+	});
+	assert!(matches!(wait(&receiver, Some(deadline)), Wake::Message(1)));
+	assert!(Instant::now() < deadline);
+	sending_thread.join().unwrap();
+
+	// (including within the last stretch, which is spun through)
+	let (sender, receiver) = crossbeam_channel::unbounded();
+	sender.send(2).unwrap();
+	assert!(matches!(wait(&receiver, Some(Instant::now() + LIMIT / 2)), Wake::Message(2)));
+}
+
+#[test]
+pub fn deadline_is_never_early() {
+	let (_sender, receiver) = crossbeam_channel::unbounded::<()>();
+
+	for duration in [Duration::ZERO, Duration::from_millis(1), LIMIT / 2, LIMIT, LIMIT + Duration::from_millis(5), Duration::from_millis(40)] {
+		let deadline = Instant::now() + duration;
+		assert!(matches!(wait(&receiver, Some(deadline)), Wake::Deadline));
+		assert!(Instant::now() >= deadline);
+	}
+}
+
+#[test]
+pub fn disconnected() {
+	let (sender, receiver) = crossbeam_channel::unbounded::<()>();
+	drop(sender);
+
+	assert!(matches!(wait(&receiver, Some(Instant::now() + Duration::from_millis(500))), Wake::Disconnected));
+	assert!(matches!(wait(&receiver, None), Wake::Disconnected));
+}
@@ -0,0 +1,14 @@
+#[derive(Debug)]
+pub enum Error {
+	/// The sample rate must be above zero.
+	InvalidSampleRate,
+	/// The performance loops (or repeats) forever, so cannot be rendered without a maximum duration.
+	Endless,
+	/// The rendered audio is too long (or its sample rate too high) to be described by a WAV file's 32-bit sizes.
+	TooLarge,
+	/// The data given is not a well-formed SoundFont 2 file.
+	#[cfg(feature = "soundfont")]
+	InvalidSoundFont,
+	/// The rendered audio could not be written out (or a SoundFont file could not be read).
+	Io(std::io::Error)
+}
@@ -0,0 +1,348 @@
+use std::{io::Write, time::Duration};
+#[cfg(feature = "soundfont")]
+use std::sync::Arc;
+
+use midly::MidiMessage;
+
+use crate::{
+	score::{Event, MidiEvent},
+	Performer
+};
+
+mod patch;
+use patch::Patch;
+mod voice;
+use voice::Voice;
+mod wav;
+#[cfg(feature = "soundfont")]
+mod sound_font;
+#[cfg(feature = "soundfont")]
+pub use sound_font::SoundFont;
+mod error;
+pub use error::Error;
+
+#[cfg(test)]
+mod tests;
+
+const CHANNEL_COUNT:usize = 16;
+const DRUM_CHANNEL:u8 = 9;
+const MAXIMUM_VOICE_COUNT:usize = 64;
+const PITCH_BEND_RANGE:f32 = 2.0;
+const MASTER_GAIN:f32 = 0.25;
+/// How long a performance is left to ring out after its last event, at most.
+const MAXIMUM_TAIL_DURATION:Duration = Duration::from_secs(2);
+
+#[derive(Debug, Copy, Clone)]
+struct ChannelState {
+	bank: u16,
+	program: u8,
+	volume: u8,
+	expression: u8,
+	pan: u8,
+	sustain: bool,
+	/// In semitones.
+	pitch_bend: f32,
+}
+
+impl Default for ChannelState {
+	fn default() -> ChannelState {
+		ChannelState {
+			bank: 0,
+			program: 0,
+			volume: 100,
+			expression: 127,
+			pan: 64,
+			sustain: false,
+			pitch_bend: 0.0
+		}
+	}
+}
+
+impl ChannelState {
+	/// The gains of the left and right outputs, for a voice with the given pan of its own.
+	fn gains(&self, voice_pan:f32) -> (f32, f32) {
+		let level = (f32::from(self.volume) / 127.0) * (f32::from(self.expression) / 127.0);
+		let pan = (f32::from(self.pan) / 127.0 + voice_pan / 2.0).clamp(0.0, 1.0);
+		let angle = pan * std::f32::consts::FRAC_PI_2;
+		(level * angle.cos(), level * angle.sin())
+	}
+}
+
+/// A simple General MIDI synthesizer, which turns [`MidiEvent`]s into stereo audio.
+///
+/// By default, each program family is approximated with a basic waveform and envelope, and the percussion channel
+/// (channel 10) with pitched sine sweeps and filtered noise; it is meant for previewing a score, not for fidelity.
+/// With the `soundfont` feature, the synth can instead play the sampled instruments of a [`SoundFont`].
+pub struct Synth {
+	sample_rate: u32,
+	channels: [ChannelState; CHANNEL_COUNT],
+	voices: Vec<Voice>,
+	#[cfg(feature = "soundfont")]
+	sound_font: Option<Arc<SoundFont>>,
+}
+
+impl Synth {
+	/// Create a new synthesizer producing audio at the given sample rate (in Hz).
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidSampleRate`] if the sample rate is zero.
+	pub fn new(sample_rate:u32) -> Result<Synth, crate::Error> {
+		if sample_rate == 0 {
+			return Err(Error::InvalidSampleRate.into());
+		}
+
+		Ok(
+			Synth {
+				sample_rate,
+				channels: [ChannelState::default(); CHANNEL_COUNT],
+				voices: Vec::with_capacity(MAXIMUM_VOICE_COUNT),
+				#[cfg(feature = "soundfont")]
+				sound_font: None,
+			}
+		)
+	}
+
+	/// Create a new synthesizer producing audio at the given sample rate (in Hz), which plays the instruments of the
+	/// given [`SoundFont`]. Notes for which the SoundFont has no preset fall back to the built-in sounds.
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidSampleRate`] if the sample rate is zero.
+	#[cfg(feature = "soundfont")]
+	pub fn with_sound_font(sample_rate:u32, sound_font:Arc<SoundFont>) -> Result<Synth, crate::Error> {
+		let mut synth = Synth::new(sample_rate)?;
+		synth.sound_font = Some(sound_font);
+		Ok(synth)
+	}
+
+	pub fn get_sample_rate(&self) -> u32 {
+		self.sample_rate
+	}
+
+	/// Whether no notes are sounding.
+	pub fn is_silent(&self) -> bool {
+		self.voices.is_empty()
+	}
+}
+
+impl Synth {
+	/// Act upon a [`MidiEvent`].
+	pub fn receive(&mut self, midi_event:&MidiEvent) {
+		let channel = u8::from(midi_event.channel);
+		let channel_state = &mut self.channels[usize::from(channel)];
+
+		match midi_event.message {
+			MidiMessage::NoteOn { key, vel } if vel > 0 => self.note_on(channel, u8::from(key), u8::from(vel)),
+			MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => self.note_off(channel, u8::from(key)),
+			MidiMessage::ProgramChange { program } => channel_state.program = u8::from(program),
+			MidiMessage::PitchBend { bend } => channel_state.pitch_bend = bend.as_f32() * PITCH_BEND_RANGE,
+			MidiMessage::Controller { controller, value } => {
+				let value = u8::from(value);
+				match u8::from(controller) {
+					// bank select
+					0 => channel_state.bank = u16::from(value),
+					7 => channel_state.volume = value,
+					10 => channel_state.pan = value,
+					11 => channel_state.expression = value,
+					64 => {
+						channel_state.sustain = value >= 64;
+						if !channel_state.sustain {
+							self.voices
+								.iter_mut()
+								.filter(|voice| voice.channel == channel && voice.sustained)
+								.for_each(Voice::release);
+						}
+					},
+					// all sound off
+					120 => self.voices.retain(|voice| voice.channel != channel),
+					// reset all controllers
+					121 => {
+						*channel_state = ChannelState {
+							bank: channel_state.bank,
+							program: channel_state.program,
+							volume: channel_state.volume,
+							pan: channel_state.pan,
+							..ChannelState::default()
+						};
+					},
+					// all notes off
+					123 => {
+						self.voices
+							.iter_mut()
+							.filter(|voice| voice.channel == channel)
+							.for_each(Voice::release);
+					},
+					_ => {}
+				}
+			},
+			MidiMessage::Aftertouch { .. } | MidiMessage::ChannelAftertouch { .. } => {}
+		}
+	}
+
+	fn note_on(&mut self, channel:u8, key:u8, velocity:u8) {
+		// (a key struck again cuts off the note it is already playing)
+		self.voices
+			.iter_mut()
+			.filter(|voice| voice.channel == channel && voice.key == key)
+			.for_each(Voice::release);
+
+		for voice in self.voices_for(channel, key, velocity) {
+			// (when out of voices, the oldest one is stolen)
+			if self.voices.len() >= MAXIMUM_VOICE_COUNT {
+				let index = self.voices.iter().position(Voice::is_released).unwrap_or(0);
+				self.voices.remove(index);
+			}
+
+			self.voices.push(voice);
+		}
+	}
+
+	/// Create the voices which sound a note.
+	fn voices_for(&self, channel:u8, key:u8, velocity:u8) -> Vec<Voice> {
+		let channel_state = &self.channels[usize::from(channel)];
+
+		#[cfg(feature = "soundfont")]
+		if let Some(sound_font) = &self.sound_font {
+			let bank = if channel == DRUM_CHANNEL { sound_font::PERCUSSION_BANK } else { channel_state.bank };
+			let voices:Vec<Voice> = sound_font
+				.sample_players(bank, channel_state.program, key, velocity, self.sample_rate)
+				.into_iter()
+				.map(|(sample_player, envelope, gain, pan)| Voice::from_sample(channel, key, velocity, sample_player, envelope, gain, pan))
+				.collect();
+			if !voices.is_empty() {
+				return voices;
+			}
+		}
+
+		let patch = if channel == DRUM_CHANNEL {
+			Patch::for_drum(key)
+		} else {
+			Patch::for_program(channel_state.program)
+		};
+		vec![Voice::new(channel, key, velocity, patch)]
+	}
+
+	fn note_off(&mut self, channel:u8, key:u8) {
+		let sustain = self.channels[usize::from(channel)].sustain;
+
+		for voice in self.voices.iter_mut().filter(|voice| voice.channel == channel && voice.key == key && !voice.is_released()) {
+			if sustain {
+				voice.sustained = true;
+			} else {
+				voice.release();
+			}
+		}
+	}
+
+	/// Fill the buffer with interleaved stereo samples (left, then right) of the sounding notes.
+	pub fn render(&mut self, buffer:&mut [f32]) {
+		let sample_duration = 1.0 / self.sample_rate as f32;
+
+		for frame in buffer.chunks_mut(2) {
+			let (mut left, mut right) = (0.0, 0.0);
+			for voice in &mut self.voices {
+				let channel_state = &self.channels[usize::from(voice.channel)];
+				let sample = voice.next_sample(sample_duration, channel_state.pitch_bend);
+				let (left_gain, right_gain) = channel_state.gains(voice.pan);
+				left += sample * left_gain;
+				right += sample * right_gain;
+			}
+
+			// (soft clipping keeps dense passages from wrapping around)
+			frame[0] = (left * MASTER_GAIN).tanh();
+			if let Some(sample) = frame.get_mut(1) {
+				*sample = (right * MASTER_GAIN).tanh();
+			}
+
+			self.voices.retain(|voice| !voice.is_finished());
+		}
+	}
+}
+
+impl Performer {
+	/// Render the performance to a 16-bit stereo WAV file at the given sample rate, using the built-in [Synth].
+	///
+	/// (See [`Performer::render_to_samples`] for how the performance is rendered.)
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidSampleRate`] if the sample rate is zero.
+	/// - Will return an [`Error::TooLarge`] if the audio is too long (or the sample rate too high) for a WAV file.
+	/// - Will return an [`Error::Io`] if the audio could not be written.
+	/// - Will return any of the errors of [`Performer::render_to_samples`].
+	pub fn render_to_wav(&self, writer:impl Write, sample_rate:u32, maximum_duration:Option<Duration>) -> Result<(), crate::Error> {
+		self.render_to_wav_with_synth(writer, &mut Synth::new(sample_rate)?, maximum_duration)
+	}
+
+	/// Render the performance to a 16-bit stereo WAV file through the given [Synth], at its sample rate.
+	///
+	/// (See [`Performer::render_to_samples`] for how the performance is rendered.)
+	///
+	/// # Errors
+	/// - Will return an [`Error::TooLarge`] if the audio is too long (or the sample rate too high) for a WAV file.
+	/// - Will return an [`Error::Io`] if the audio could not be written.
+	/// - Will return any of the errors of [`Performer::render_to_samples`].
+	pub fn render_to_wav_with_synth(&self, writer:impl Write, synth:&mut Synth, maximum_duration:Option<Duration>) -> Result<(), crate::Error> {
+		let samples = self.render_to_samples(synth, maximum_duration)?;
+		wav::write(std::io::BufWriter::new(writer), synth.get_sample_rate(), &samples).map_err(Into::into)
+	}
+
+	/// Render the performance through the given [Synth], as interleaved stereo samples (left, then right) at its sample
+	/// rate.
+	///
+	/// The performance is rendered as [`Performer::render`] describes, and is followed by up to two seconds of silence
+	/// for the last notes to ring out. If a `maximum_duration` is given, the audio is cut off at that point.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Endless`] if the performance (or its loop region) loops or repeats forever, and no
+	///   maximum duration was given.
+	/// - Will return any of the errors of [`Performer::render`], or an [`Error::Engine`](crate::Error::Engine) if the
+	///   engine encounters an error while rendering.
+	pub fn render_to_samples(&self, synth:&mut Synth, maximum_duration:Option<Duration>) -> Result<Vec<f32>, crate::Error> {
+		if maximum_duration.is_none() && (self.is_looping() || self.is_repeating_all() || self.loop_region.is_some_and(|region| region.repeats.is_none())) {
+			return Err(Error::Endless.into());
+		}
+
+		let sample_rate = synth.get_sample_rate();
+		let frame_limit = maximum_duration.map_or(usize::MAX, |duration| frames_in(duration, sample_rate));
+		let mut samples:Vec<f32> = vec![];
+
+		let mut render = self.render()?;
+		for (time, _track, event) in render.by_ref() {
+			let frame = frames_in(time, sample_rate).min(frame_limit);
+			synth.render_until(&mut samples, frame);
+			if frame == frame_limit {
+				break;
+			}
+
+			if let Event::Midi(midi_event) = event {
+				synth.receive(&midi_event);
+			}
+		}
+		if let Some(err) = render.take_error() {
+			return Err(crate::Error::Engine(err));
+		}
+
+		// (let the last notes ring out)
+		let tail_end = (samples.len() / 2 + frames_in(MAXIMUM_TAIL_DURATION, sample_rate)).min(frame_limit);
+		while !synth.is_silent() && samples.len() / 2 < tail_end {
+			let frame = (samples.len() / 2 + sample_rate as usize / 10).min(tail_end);
+			synth.render_until(&mut samples, frame);
+		}
+
+		Ok(samples)
+	}
+}
+
+impl Synth {
+	/// Render interleaved stereo samples onto the end of `samples` until it holds the given number of frames.
+	fn render_until(&mut self, samples:&mut Vec<f32>, frame:usize) {
+		let start = samples.len();
+		if frame * 2 > start {
+			samples.resize(frame * 2, 0.0);
+			self.render(&mut samples[start..]);
+		}
+	}
+}
+
+fn frames_in(duration:Duration, sample_rate:u32) -> usize {
+	(duration.as_secs_f64() * f64::from(sample_rate)).round() as usize
+}
@@ -0,0 +1,166 @@
+/// The basic shape of a sound.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Waveform {
+	Sine,
+	Triangle,
+	Square,
+	Sawtooth,
+	Noise,
+}
+
+impl Waveform {
+	/// The value of the waveform at the given phase (from 0 to 1), using the given noise sample for [`Waveform::Noise`].
+	pub fn sample(self, phase:f32, noise:f32) -> f32 {
+		match self {
+			Waveform::Sine => (phase * std::f32::consts::TAU).sin(),
+			Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+			Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+			Waveform::Sawtooth => 2.0 * phase - 1.0,
+			Waveform::Noise => noise,
+		}
+	}
+}
+
+/// A delay, attack, hold, decay, sustain and release envelope. Times are in seconds, and the sustain level runs from 0
+/// to 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Envelope {
+	pub delay: f32,
+	pub attack: f32,
+	pub hold: f32,
+	pub decay: f32,
+	pub sustain: f32,
+	pub release: f32,
+}
+
+impl Envelope {
+	const fn new(attack:f32, decay:f32, sustain:f32, release:f32) -> Envelope {
+		Envelope {
+			delay: 0.0,
+			attack,
+			hold: 0.0,
+			decay,
+			sustain,
+			release
+		}
+	}
+
+	/// The level of the envelope the given time after the note began, while it is held.
+	pub fn level_while_held(&self, time:f32) -> f32 {
+		let time = time - self.delay;
+		if time < 0.0 {
+			0.0
+		} else if time < self.attack {
+			time / self.attack
+		} else if time < self.attack + self.hold {
+			1.0
+		} else if time < self.attack + self.hold + self.decay {
+			1.0 - (1.0 - self.sustain) * (time - self.attack - self.hold) / self.decay
+		} else {
+			self.sustain
+		}
+	}
+
+	/// The time (in seconds) after which a held note has settled at the sustain level.
+	pub fn settled_at(&self) -> f32 {
+		self.delay + self.attack + self.hold + self.decay
+	}
+}
+
+/// The recipe for the sound of an instrument.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Patch {
+	pub waveform: Waveform,
+	pub envelope: Envelope,
+	/// The gain applied to this patch, balancing it against the others.
+	pub gain: f32,
+	/// For drums, a fixed frequency (in Hz) to sound at instead of the pitch of the key, and the frequency it falls to
+	/// over the course of the decay.
+	pub fixed_frequency: Option<(f32, f32)>,
+}
+
+impl Patch {
+	const fn new(waveform:Waveform, envelope:Envelope, gain:f32) -> Patch {
+		Patch {
+			waveform,
+			envelope,
+			gain,
+			fixed_frequency: None
+		}
+	}
+
+	const fn drum(waveform:Waveform, start_frequency:f32, end_frequency:f32, decay:f32, gain:f32) -> Patch {
+		Patch {
+			waveform,
+			envelope: Envelope::new(0.001, decay, 0.0, decay),
+			gain,
+			fixed_frequency: Some((start_frequency, end_frequency))
+		}
+	}
+
+	/// The patch for a General MIDI program, approximated by the family (group of eight programs) it belongs to.
+	pub fn for_program(program:u8) -> Patch {
+		match program / 8 {
+			// piano
+			0 => Patch::new(Waveform::Triangle, Envelope::new(0.002, 1.5, 0.0, 0.2), 1.0),
+			// chromatic percussion
+			1 => Patch::new(Waveform::Sine, Envelope::new(0.001, 0.8, 0.0, 0.3), 1.0),
+			// organ
+			2 => Patch::new(Waveform::Square, Envelope::new(0.01, 0.0, 1.0, 0.05), 0.35),
+			// guitar
+			3 => Patch::new(Waveform::Sawtooth, Envelope::new(0.002, 1.0, 0.1, 0.15), 0.5),
+			// bass
+			4 => Patch::new(Waveform::Triangle, Envelope::new(0.005, 0.6, 0.5, 0.1), 1.0),
+			// strings and ensemble
+			5 | 6 => Patch::new(Waveform::Sawtooth, Envelope::new(0.15, 0.2, 0.8, 0.3), 0.4),
+			// brass
+			7 => Patch::new(Waveform::Sawtooth, Envelope::new(0.04, 0.2, 0.7, 0.15), 0.45),
+			// reed
+			8 => Patch::new(Waveform::Square, Envelope::new(0.03, 0.1, 0.8, 0.1), 0.35),
+			// pipe
+			9 => Patch::new(Waveform::Sine, Envelope::new(0.05, 0.1, 0.9, 0.15), 0.9),
+			// synth lead
+			10 => Patch::new(Waveform::Square, Envelope::new(0.005, 0.1, 0.8, 0.1), 0.35),
+			// synth pad
+			11 => Patch::new(Waveform::Triangle, Envelope::new(0.4, 0.5, 0.8, 0.8), 0.8),
+			// synth effects
+			12 => Patch::new(Waveform::Sawtooth, Envelope::new(0.2, 0.5, 0.6, 0.6), 0.4),
+			// ethnic
+			13 => Patch::new(Waveform::Sawtooth, Envelope::new(0.002, 0.7, 0.0, 0.2), 0.5),
+			// percussive
+			14 => Patch::new(Waveform::Sine, Envelope::new(0.001, 0.4, 0.0, 0.1), 1.0),
+			// sound effects
+			_ => Patch::new(Waveform::Noise, Envelope::new(0.05, 0.5, 0.3, 0.3), 0.3),
+		}
+	}
+
+	/// The patch for a key of the General MIDI percussion channel.
+	pub fn for_drum(key:u8) -> Patch {
+		match key {
+			// bass drums
+			35 | 36 => Patch::drum(Waveform::Sine, 150.0, 45.0, 0.3, 1.6),
+			// side stick, snares and claps
+			37..=40 => Patch::drum(Waveform::Noise, 0.0, 0.0, 0.18, 0.7),
+			// toms
+			41 | 43 | 45 | 47 | 48 | 50 => {
+				let frequency = 80.0 + f32::from(key - 41) * 15.0;
+				Patch::drum(Waveform::Sine, frequency * 1.5, frequency, 0.35, 1.2)
+			},
+			// closed and pedal hi-hats
+			42 | 44 => Patch::drum(Waveform::Noise, 0.0, 0.0, 0.05, 0.35),
+			// open hi-hat
+			46 => Patch::drum(Waveform::Noise, 0.0, 0.0, 0.3, 0.3),
+			// cymbals
+			49 | 51 | 52 | 53 | 55 | 57 | 59 => Patch::drum(Waveform::Noise, 0.0, 0.0, 1.0, 0.25),
+			// cowbell, woodblocks, and other pitched percussion
+			56 | 76 | 77 => Patch::drum(Waveform::Square, 800.0, 780.0, 0.1, 0.3),
+			// bongos, congas, timbales and the like
+			60..=68 => {
+				let frequency = 180.0 + f32::from(key - 60) * 25.0;
+				Patch::drum(Waveform::Sine, frequency * 1.3, frequency, 0.2, 0.9)
+			},
+			// everything else (shakers, whistles, etc.)
+			_ => Patch::drum(Waveform::Noise, 0.0, 0.0, 0.12, 0.3),
+		}
+	}
+}
@@ -0,0 +1,400 @@
+use std::{ops::RangeInclusive, path::Path, sync::Arc};
+
+use super::{patch::Envelope, Error};
+
+mod riff;
+use riff::{read_u16, read_u32, records, Chunk};
+mod sample_player;
+pub use sample_player::{LoopMode, SamplePlayer};
+
+#[cfg(test)]
+mod tests;
+
+/// The bank holding the percussion kits.
+pub const PERCUSSION_BANK:u16 = 128;
+
+const GENERATOR_COUNT:usize = 61;
+
+//generators
+	const START_ADDRESS_OFFSET:usize = 0;
+	const END_ADDRESS_OFFSET:usize = 1;
+	const START_LOOP_ADDRESS_OFFSET:usize = 2;
+	const END_LOOP_ADDRESS_OFFSET:usize = 3;
+	const START_ADDRESS_COARSE_OFFSET:usize = 4;
+	const END_ADDRESS_COARSE_OFFSET:usize = 12;
+	const PAN:usize = 17;
+	const DELAY_VOLUME_ENVELOPE:usize = 33;
+	const ATTACK_VOLUME_ENVELOPE:usize = 34;
+	const HOLD_VOLUME_ENVELOPE:usize = 35;
+	const DECAY_VOLUME_ENVELOPE:usize = 36;
+	const SUSTAIN_VOLUME_ENVELOPE:usize = 37;
+	const RELEASE_VOLUME_ENVELOPE:usize = 38;
+	const INSTRUMENT:usize = 41;
+	const KEY_RANGE:usize = 43;
+	const VELOCITY_RANGE:usize = 44;
+	const START_LOOP_ADDRESS_COARSE_OFFSET:usize = 45;
+	const INITIAL_ATTENUATION:usize = 48;
+	const END_LOOP_ADDRESS_COARSE_OFFSET:usize = 50;
+	const COARSE_TUNE:usize = 51;
+	const FINE_TUNE:usize = 52;
+	const SAMPLE_ID:usize = 53;
+	const SAMPLE_MODES:usize = 54;
+	const SCALE_TUNING:usize = 56;
+	const OVERRIDING_ROOT_KEY:usize = 58;
+
+/// The generators which a preset zone may adjust, by adding to the instrument's values.
+const PRESET_GENERATORS:[usize; 11] = [
+	PAN,
+	DELAY_VOLUME_ENVELOPE, ATTACK_VOLUME_ENVELOPE, HOLD_VOLUME_ENVELOPE, DECAY_VOLUME_ENVELOPE, SUSTAIN_VOLUME_ENVELOPE, RELEASE_VOLUME_ENVELOPE,
+	INITIAL_ATTENUATION,
+	COARSE_TUNE, FINE_TUNE,
+	SCALE_TUNING,
+];
+
+type Generators = [i32; GENERATOR_COUNT];
+
+/// A bank of sampled instruments, loaded from a SoundFont 2 (`.sf2`) file, for the [Synth](super::Synth) to play.
+#[derive(Debug)]
+pub struct SoundFont {
+	samples: Arc<[f32]>,
+	presets: Vec<Preset>,
+}
+
+#[derive(Debug)]
+struct Preset {
+	bank: u16,
+	program: u16,
+	regions: Vec<Region>,
+}
+
+/// The sample (and how to play it) for a range of keys and velocities of a preset, with the generators of its preset
+/// and instrument zones already combined.
+#[derive(Debug)]
+struct Region {
+	keys: RangeInclusive<u8>,
+	velocities: RangeInclusive<u8>,
+	start: usize,
+	end: usize,
+	loop_start: usize,
+	loop_end: usize,
+	loop_mode: LoopMode,
+	sample_rate: u32,
+	root_key: u8,
+	/// In cents.
+	tuning: i32,
+	/// In cents per key.
+	scale_tuning: i32,
+	/// In centibels.
+	attenuation: i32,
+	/// From -1 for the left to 1 for the right.
+	pan: f32,
+	envelope: Envelope,
+}
+
+struct SampleHeader {
+	start: u32,
+	end: u32,
+	loop_start: u32,
+	loop_end: u32,
+	sample_rate: u32,
+	original_pitch: u8,
+	pitch_correction: i8,
+}
+
+impl SoundFont {
+	/// Load a SoundFont from a `.sf2` file.
+	///
+	/// # Errors
+	/// - Will return an [`Error::Io`] if the file could not be read.
+	/// - Will return an [`Error::InvalidSoundFont`] if the file is not a well-formed SoundFont.
+	pub fn load(path:impl AsRef<Path>) -> Result<SoundFont, crate::Error> {
+		let data = std::fs::read(path).map_err(Error::Io)?;
+		SoundFont::new(&data)
+	}
+
+	/// Parse a SoundFont from the contents of a `.sf2` file.
+	///
+	/// # Errors
+	/// - Will return an [`Error::InvalidSoundFont`] if the data is not a well-formed SoundFont.
+	pub fn new(data:&[u8]) -> Result<SoundFont, crate::Error> {
+		Ok(SoundFont::parse(data)?)
+	}
+
+	fn parse(data:&[u8]) -> Result<SoundFont, Error> {
+		//header
+			if data.get(0..4) != Some(b"RIFF") || data.get(8..12) != Some(b"sfbk") {
+				return Err(Error::InvalidSoundFont);
+			}
+			let length = read_u32(data, 4)? as usize;
+			let chunks = Chunk::split(data.get(12..4 + 4 + length).ok_or(Error::InvalidSoundFont)?)?;
+
+		//samples
+			let sample_data = Chunk::find(&Chunk::list(&chunks, b"sdta")?, b"smpl")?;
+			let samples:Arc<[f32]> = sample_data
+				.chunks_exact(2)
+				.map(|bytes| f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32768.0)
+				.collect();
+
+		//presets, instruments and sample headers
+			let hydra = Chunk::list(&chunks, b"pdta")?;
+
+			let sample_headers = records(Chunk::find(&hydra, b"shdr")?, 46)?
+				.into_iter()
+				.map(|record| Ok(
+					SampleHeader {
+						start: read_u32(record, 20)?,
+						end: read_u32(record, 24)?,
+						loop_start: read_u32(record, 28)?,
+						loop_end: read_u32(record, 32)?,
+						sample_rate: read_u32(record, 36)?,
+						original_pitch: record[40],
+						pitch_correction: record[41] as i8,
+					}
+				))
+				.collect::<Result<Vec<_>, Error>>()?;
+
+			let instrument_zones = Zone::collect(
+				&records(Chunk::find(&hydra, b"inst")?, 22)?,
+				20,
+				&records(Chunk::find(&hydra, b"ibag")?, 4)?,
+				&records(Chunk::find(&hydra, b"igen")?, 4)?,
+				SAMPLE_ID,
+			)?;
+
+			let preset_records = records(Chunk::find(&hydra, b"phdr")?, 38)?;
+			let preset_zones = Zone::collect(
+				&preset_records,
+				24,
+				&records(Chunk::find(&hydra, b"pbag")?, 4)?,
+				&records(Chunk::find(&hydra, b"pgen")?, 4)?,
+				INSTRUMENT,
+			)?;
+
+		//combine them into regions
+			let presets = preset_records
+				.iter()
+				.zip(preset_zones)
+				.map(|(record, (global, zones))| Ok(
+					Preset {
+						program: read_u16(record, 20)?,
+						bank: read_u16(record, 22)?,
+						regions: zones
+							.iter()
+							.filter_map(|zone| {
+								let preset_generators = zone.apply_to(global.as_ref(), Zone::default_preset_generators());
+								let (instrument_global, instrument_zones) = instrument_zones.get(usize::try_from(preset_generators[INSTRUMENT]).ok()?)?;
+								Some((preset_generators, instrument_global, instrument_zones))
+							})
+							.flat_map(|(preset_generators, instrument_global, instrument_zones)| {
+								instrument_zones
+									.iter()
+									.filter_map(|zone| {
+										let generators = zone.apply_to(instrument_global.as_ref(), Zone::default_generators());
+										let sample_header = sample_headers.get(usize::try_from(generators[SAMPLE_ID]).ok()?)?;
+										Region::new(generators, &preset_generators, sample_header)
+									})
+									.collect::<Vec<_>>()
+							})
+							.collect()
+					}
+				))
+				.collect::<Result<Vec<_>, Error>>()?;
+
+		Ok(
+			SoundFont {
+				samples,
+				presets
+			}
+		)
+	}
+}
+
+impl SoundFont {
+	/// Whether the SoundFont has a preset for the given bank and program.
+	pub fn has_preset(&self, bank:u16, program:u8) -> bool {
+		self.presets.iter().any(|preset| preset.bank == bank && preset.program == u16::from(program))
+	}
+
+	/// Create the sample players (along with their envelopes, gains and pans) that sound the given key of the given
+	/// bank and program. If the SoundFont has no such preset, the same program of the first bank (or, for percussion,
+	/// the first kit) is used instead.
+	pub fn sample_players(&self, bank:u16, program:u8, key:u8, velocity:u8, output_sample_rate:u32) -> Vec<(SamplePlayer, Envelope, f32, f32)> {
+		let fallback = if bank >= PERCUSSION_BANK { (PERCUSSION_BANK, 0) } else { (0, u16::from(program)) };
+		let Some(preset) = self.presets
+			.iter()
+			.find(|preset| preset.bank == bank && preset.program == u16::from(program))
+			.or_else(|| self.presets.iter().find(|preset| (preset.bank, preset.program) == fallback))
+		else {
+			return vec![];
+		};
+
+		preset.regions
+			.iter()
+			.filter(|region| region.keys.contains(&key) && region.velocities.contains(&velocity))
+			.map(|region| {
+				let cents = (i32::from(key) - i32::from(region.root_key)) * region.scale_tuning + region.tuning;
+				let step = (f64::from(cents) / 1200.0).exp2() * f64::from(region.sample_rate) / f64::from(output_sample_rate);
+
+				(
+					SamplePlayer::new(
+						Arc::clone(&self.samples),
+						region.start,
+						region.end,
+						(region.loop_start, region.loop_end),
+						region.loop_mode,
+						step
+					),
+					region.envelope,
+					centibels_to_gain(region.attenuation),
+					region.pan,
+				)
+			})
+			.collect()
+	}
+}
+
+/// The generators of a zone of a preset or instrument.
+struct Zone {
+	generators: Vec<(usize, i32)>,
+}
+
+impl Zone {
+	/// Gather the zones of each preset or instrument (dropping the terminal record), separating out any global zone.
+	#[allow(clippy::type_complexity)]
+	fn collect(headers:&[&[u8]], bag_index_offset:usize, bags:&[&[u8]], generators:&[&[u8]], terminal_generator:usize) -> Result<Vec<(Option<Zone>, Vec<Zone>)>, Error> {
+		let bag_indices = headers.iter().map(|record| read_u16(record, bag_index_offset).map(usize::from)).collect::<Result<Vec<_>, Error>>()?;
+		let generator_indices = bags.iter().map(|record| read_u16(record, 0).map(usize::from)).collect::<Result<Vec<_>, Error>>()?;
+
+		bag_indices
+			.windows(2)
+			.map(|bag_range| {
+				let mut zones = generator_indices
+					.get(bag_range[0]..=bag_range[1])
+					.ok_or(Error::InvalidSoundFont)?
+					.windows(2)
+					.map(|generator_range| {
+						let generators = generators
+							.get(generator_range[0]..generator_range[1])
+							.ok_or(Error::InvalidSoundFont)?
+							.iter()
+							.map(|record| Ok((usize::from(read_u16(record, 0)?), i32::from(read_u16(record, 2)? as i16))))
+							.filter(|generator| !matches!(generator, Ok((operator, _)) if *operator >= GENERATOR_COUNT))
+							.collect::<Result<Vec<_>, Error>>()?;
+						Ok(Zone { generators })
+					})
+					.collect::<Result<Vec<_>, Error>>()?;
+
+				// (the first zone is a global one if it doesn't lead anywhere; any other such zones are ignored)
+				let global = if zones.first().is_some_and(|zone| !zone.is_terminated(terminal_generator)) {
+					Some(zones.remove(0))
+				} else {
+					None
+				};
+				zones.retain(|zone| zone.is_terminated(terminal_generator));
+
+				Ok((global, zones))
+			})
+			.collect()
+	}
+
+	fn is_terminated(&self, terminal_generator:usize) -> bool {
+		self.generators.last().is_some_and(|(operator, _)| *operator == terminal_generator)
+	}
+
+	/// The values of a preset's generators, which (apart from the ranges) are added to those of its instruments.
+	fn default_preset_generators() -> Generators {
+		let mut generators = [0; GENERATOR_COUNT];
+		generators[KEY_RANGE] = 0x7F00;
+		generators[VELOCITY_RANGE] = 0x7F00;
+		generators
+	}
+
+	fn default_generators() -> Generators {
+		let mut generators = [0; GENERATOR_COUNT];
+		for generator in [DELAY_VOLUME_ENVELOPE, ATTACK_VOLUME_ENVELOPE, HOLD_VOLUME_ENVELOPE, DECAY_VOLUME_ENVELOPE, RELEASE_VOLUME_ENVELOPE] {
+			generators[generator] = -12_000;
+		}
+		generators[KEY_RANGE] = 0x7F00;
+		generators[VELOCITY_RANGE] = 0x7F00;
+		generators[SCALE_TUNING] = 100;
+		generators[OVERRIDING_ROOT_KEY] = -1;
+		generators
+	}
+
+	/// Apply the global zone's generators and then this zone's own (which take precedence) to the given values.
+	fn apply_to(&self, global:Option<&Zone>, mut generators:Generators) -> Generators {
+		for (operator, amount) in global.into_iter().chain([self]).flat_map(|zone| &zone.generators) {
+			generators[*operator] = *amount;
+		}
+		generators
+	}
+}
+
+impl Region {
+	fn new(generators:Generators, preset_generators:&Generators, sample_header:&SampleHeader) -> Option<Region> {
+		let keys = intersect(&range(generators[KEY_RANGE]), &range(preset_generators[KEY_RANGE]))?;
+		let velocities = intersect(&range(generators[VELOCITY_RANGE]), &range(preset_generators[VELOCITY_RANGE]))?;
+
+		let mut generators = generators;
+		for generator in PRESET_GENERATORS {
+			generators[generator] += preset_generators[generator];
+		}
+
+		let offset = |base:u32, fine:usize, coarse:usize| {
+			usize::try_from(i64::from(base) + i64::from(generators[fine]) + i64::from(generators[coarse]) * 32768).unwrap_or(0)
+		};
+
+		Some(
+			Region {
+				keys,
+				velocities,
+				start: offset(sample_header.start, START_ADDRESS_OFFSET, START_ADDRESS_COARSE_OFFSET),
+				end: offset(sample_header.end, END_ADDRESS_OFFSET, END_ADDRESS_COARSE_OFFSET),
+				loop_start: offset(sample_header.loop_start, START_LOOP_ADDRESS_OFFSET, START_LOOP_ADDRESS_COARSE_OFFSET),
+				loop_end: offset(sample_header.loop_end, END_LOOP_ADDRESS_OFFSET, END_LOOP_ADDRESS_COARSE_OFFSET),
+				loop_mode: match generators[SAMPLE_MODES] & 3 {
+					1 => LoopMode::Continuous,
+					3 => LoopMode::UntilRelease,
+					_ => LoopMode::None,
+				},
+				sample_rate: sample_header.sample_rate.max(1),
+				root_key: match u8::try_from(generators[OVERRIDING_ROOT_KEY]) {
+					Ok(key) if key <= 127 => key,
+					_ if sample_header.original_pitch <= 127 => sample_header.original_pitch,
+					_ => 60,
+				},
+				tuning: generators[COARSE_TUNE] * 100 + generators[FINE_TUNE] + i32::from(sample_header.pitch_correction),
+				scale_tuning: generators[SCALE_TUNING],
+				attenuation: generators[INITIAL_ATTENUATION].max(0),
+				pan: (generators[PAN] as f32 / 500.0).clamp(-1.0, 1.0),
+				envelope: Envelope {
+					delay: timecents_to_seconds(generators[DELAY_VOLUME_ENVELOPE]),
+					attack: timecents_to_seconds(generators[ATTACK_VOLUME_ENVELOPE]),
+					hold: timecents_to_seconds(generators[HOLD_VOLUME_ENVELOPE]),
+					decay: timecents_to_seconds(generators[DECAY_VOLUME_ENVELOPE]),
+					sustain: centibels_to_gain(generators[SUSTAIN_VOLUME_ENVELOPE]),
+					release: timecents_to_seconds(generators[RELEASE_VOLUME_ENVELOPE]),
+				},
+			}
+		)
+	}
+}
+
+/// Unpack a range generator (with the low end in its lower byte).
+fn range(amount:i32) -> RangeInclusive<u8> {
+	let [low, high, ..] = amount.to_le_bytes();
+	low.min(127)..=high.min(127)
+}
+
+fn intersect(a:&RangeInclusive<u8>, b:&RangeInclusive<u8>) -> Option<RangeInclusive<u8>> {
+	let intersection = *a.start().max(b.start())..=*a.end().min(b.end());
+	(!intersection.is_empty()).then_some(intersection)
+}
+
+fn timecents_to_seconds(timecents:i32) -> f32 {
+	(timecents as f32 / 1200.0).exp2().max(0.001)
+}
+
+fn centibels_to_gain(centibels:i32) -> f32 {
+	10.0_f32.powf(-(centibels.clamp(0, 1440) as f32) / 200.0)
+}
@@ -0,0 +1,66 @@
+//! Reading the RIFF chunks and fixed-size records that make up a SoundFont file.
+
+use super::super::Error;
+
+/// A chunk of a RIFF file: its four character identifier, and its body.
+pub struct Chunk<'a> {
+	pub id: [u8; 4],
+	pub body: &'a [u8],
+}
+
+impl<'a> Chunk<'a> {
+	/// Split data into the chunks it contains.
+	pub fn split(mut data:&'a [u8]) -> Result<Vec<Chunk<'a>>, Error> {
+		let mut chunks = vec![];
+		while data.len() >= 8 {
+			let id = data[0..4].try_into().expect("we checked that there are at least eight bytes");
+			let length = read_u32(data, 4)? as usize;
+			let body = data.get(8..8 + length).ok_or(Error::InvalidSoundFont)?;
+			chunks.push(Chunk { id, body });
+
+			// (chunks are padded to an even length)
+			data = data.get(8 + length + length % 2..).unwrap_or_default();
+		}
+		Ok(chunks)
+	}
+
+	/// The chunks within a `LIST` chunk of the given type.
+	pub fn list(chunks:&[Chunk<'a>], list_type:&[u8; 4]) -> Result<Vec<Chunk<'a>>, Error> {
+		let list = chunks
+			.iter()
+			.find(|chunk| &chunk.id == b"LIST" && chunk.body.get(0..4) == Some(list_type))
+			.ok_or(Error::InvalidSoundFont)?;
+		Chunk::split(&list.body[4..])
+	}
+
+	/// The body of the chunk with the given identifier.
+	pub fn find(chunks:&[Chunk<'a>], id:&[u8; 4]) -> Result<&'a [u8], Error> {
+		chunks
+			.iter()
+			.find(|chunk| &chunk.id == id)
+			.map(|chunk| chunk.body)
+			.ok_or(Error::InvalidSoundFont)
+	}
+}
+
+/// Split the body of a chunk into its records, each of the given size.
+pub fn records(body:&[u8], size:usize) -> Result<Vec<&[u8]>, Error> {
+	if !body.len().is_multiple_of(size) {
+		return Err(Error::InvalidSoundFont);
+	}
+	Ok(body.chunks_exact(size).collect())
+}
+
+pub fn read_u16(data:&[u8], offset:usize) -> Result<u16, Error> {
+	data
+		.get(offset..offset + 2)
+		.map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+		.ok_or(Error::InvalidSoundFont)
+}
+
+pub fn read_u32(data:&[u8], offset:usize) -> Result<u32, Error> {
+	data
+		.get(offset..offset + 4)
+		.map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+		.ok_or(Error::InvalidSoundFont)
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+/// Plays a sample from a SoundFont, looping it as its region asks.
+#[derive(Debug, Clone)]
+pub struct SamplePlayer {
+	data: Arc<[f32]>,
+	/// The position (in sample points) within the data, between points when playing at other pitches.
+	position: f64,
+	end: usize,
+	loop_start: usize,
+	loop_end: usize,
+	loop_mode: LoopMode,
+	/// The number of points to move on by per output sample, before any pitch bend.
+	step: f64,
+	finished: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LoopMode {
+	/// Play the sample once.
+	None,
+	/// Loop for as long as the note sounds.
+	Continuous,
+	/// Loop while the key is held, then play on to the end of the sample.
+	UntilRelease,
+}
+
+impl SamplePlayer {
+	pub fn new(data:Arc<[f32]>, start:usize, end:usize, loop_points:(usize, usize), loop_mode:LoopMode, step:f64) -> SamplePlayer {
+		let end = end.min(data.len());
+		let (loop_start, loop_end) = loop_points;
+		let loop_mode = if loop_start < loop_end && loop_end <= end { loop_mode } else { LoopMode::None };
+
+		SamplePlayer {
+			data,
+			position: start as f64,
+			end,
+			loop_start,
+			loop_end,
+			loop_mode,
+			step,
+			finished: start >= end,
+		}
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	/// Produce the next point of the sample, with the given pitch bend (in semitones), then move on.
+	pub fn next_sample(&mut self, pitch_bend:f32, held:bool) -> f32 {
+		if self.finished {
+			return 0.0;
+		}
+
+		let looping = match self.loop_mode {
+			LoopMode::None => false,
+			LoopMode::Continuous => true,
+			LoopMode::UntilRelease => held,
+		};
+		let limit = if looping { self.loop_end } else { self.end };
+
+		// (linear interpolation between the two nearest points)
+		let index = self.position as usize;
+		let fraction = (self.position - index as f64) as f32;
+		let next_index = if looping && index + 1 >= self.loop_end { self.loop_start } else { index + 1 };
+		let current = self.data[index];
+		let next = if next_index < limit { self.data[next_index] } else { 0.0 };
+		let sample = current + (next - current) * fraction;
+
+		self.position += self.step * f64::from(pitch_bend / 12.0).exp2();
+		if looping {
+			while self.position >= self.loop_end as f64 {
+				self.position -= (self.loop_end - self.loop_start) as f64;
+			}
+		} else if self.position >= self.end as f64 {
+			self.finished = true;
+		}
+
+		sample
+	}
+}
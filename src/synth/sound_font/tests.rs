@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use midly::{num::{u4, u7}, MidiMessage};
+
+use super::SoundFont;
+use crate::{score::MidiEvent, Synth, SynthError};
+
+const SAMPLE_RATE:u32 = 8_000;
+/// The sample is a sine wave with this many points per cycle, so it plays at 100 Hz at its root key (60) and at the
+/// output sample rate.
+const PERIOD:usize = 80;
+
+//building a SoundFont
+	fn chunk(id:&[u8; 4], body:&[u8]) -> Vec<u8> {
+		let mut chunk = id.to_vec();
+		chunk.extend_from_slice(&u32::try_from(body.len()).unwrap().to_le_bytes());
+		chunk.extend_from_slice(body);
+		if body.len() % 2 == 1 {
+			chunk.push(0);
+		}
+		chunk
+	}
+
+	fn list(list_type:&[u8; 4], chunks:&[Vec<u8>]) -> Vec<u8> {
+		let mut body = list_type.to_vec();
+		chunks.iter().for_each(|chunk| body.extend_from_slice(chunk));
+		chunk(b"LIST", &body)
+	}
+
+	fn name() -> [u8; 20] {
+		[0; 20]
+	}
+
+	/// Build a SoundFont with a looping piano (bank 0, program 0), the same an octave up in bank 1, and a one-shot
+	/// percussion kit (bank 128), all playing the same sine wave.
+	fn sound_font() -> Vec<u8> {
+		let samples:Vec<u8> = (0..PERIOD * 10)
+			.flat_map(|index| {
+				let phase = index as f32 / PERIOD as f32 * std::f32::consts::TAU;
+				((phase.sin() * 16_000.0) as i16).to_le_bytes()
+			})
+			.collect();
+
+		//presets (bank, program, and the instrument of its only zone), and instruments (generators of their only zone)
+			let presets:[(u16, u16, i16); 3] = [
+				(0, 0, 0),
+				(1, 0, 1),
+				(128, 0, 2),
+			];
+			let instruments:[Vec<(u16, i16)>; 3] = [
+				vec![(54, 1), (58, 60), (53, 0)],
+				vec![(54, 1), (58, 60), (51, 12), (53, 0)],
+				vec![(58, 60), (53, 0)],
+			];
+
+		let (mut phdr, mut pbag, mut pgen) = (vec![], vec![], vec![]);
+		for (index, (bank, program, instrument)) in presets.iter().enumerate() {
+			phdr.extend_from_slice(&name());
+			phdr.extend_from_slice(&program.to_le_bytes());
+			phdr.extend_from_slice(&bank.to_le_bytes());
+			phdr.extend_from_slice(&u16::try_from(index).unwrap().to_le_bytes());
+			phdr.extend_from_slice(&[0; 12]);
+			pbag.extend_from_slice(&u16::try_from(pgen.len() / 4).unwrap().to_le_bytes());
+			pbag.extend_from_slice(&[0; 2]);
+			pgen.extend_from_slice(&41_u16.to_le_bytes());
+			pgen.extend_from_slice(&instrument.to_le_bytes());
+		}
+		phdr.extend_from_slice(&name());
+		phdr.extend_from_slice(&[0; 4]);
+		phdr.extend_from_slice(&u16::try_from(presets.len()).unwrap().to_le_bytes());
+		phdr.extend_from_slice(&[0; 12]);
+		pbag.extend_from_slice(&u16::try_from(pgen.len() / 4).unwrap().to_le_bytes());
+		pbag.extend_from_slice(&[0; 2]);
+		pgen.extend_from_slice(&[0; 4]);
+
+		let (mut inst, mut ibag, mut igen) = (vec![], vec![], vec![]);
+		for (index, generators) in instruments.iter().enumerate() {
+			inst.extend_from_slice(&name());
+			inst.extend_from_slice(&u16::try_from(index).unwrap().to_le_bytes());
+			ibag.extend_from_slice(&u16::try_from(igen.len() / 4).unwrap().to_le_bytes());
+			ibag.extend_from_slice(&[0; 2]);
+			for (operator, amount) in generators {
+				igen.extend_from_slice(&operator.to_le_bytes());
+				igen.extend_from_slice(&amount.to_le_bytes());
+			}
+		}
+		inst.extend_from_slice(&name());
+		inst.extend_from_slice(&u16::try_from(instruments.len()).unwrap().to_le_bytes());
+		ibag.extend_from_slice(&u16::try_from(igen.len() / 4).unwrap().to_le_bytes());
+		ibag.extend_from_slice(&[0; 2]);
+		igen.extend_from_slice(&[0; 4]);
+
+		let mut shdr = vec![];
+		for (end, loop_end) in [(PERIOD * 10, PERIOD * 10), (0, 0)] {
+			shdr.extend_from_slice(&name());
+			for value in [0, end, 0, loop_end] {
+				shdr.extend_from_slice(&u32::try_from(value).unwrap().to_le_bytes());
+			}
+			shdr.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+			shdr.extend_from_slice(&[60, 0, 0, 0, 1, 0]);
+		}
+
+		let body = [
+			b"sfbk".to_vec(),
+			list(b"INFO", &[chunk(b"ifil", &[2, 0, 1, 0])]),
+			list(b"sdta", &[chunk(b"smpl", &samples)]),
+			list(b"pdta", &[
+				chunk(b"phdr", &phdr),
+				chunk(b"pbag", &pbag),
+				chunk(b"pmod", &[0; 10]),
+				chunk(b"pgen", &pgen),
+				chunk(b"inst", &inst),
+				chunk(b"ibag", &ibag),
+				chunk(b"imod", &[0; 10]),
+				chunk(b"igen", &igen),
+				chunk(b"shdr", &shdr),
+			]),
+		].concat();
+		chunk(b"RIFF", &body)
+	}
+
+//playing
+	fn event(channel:u8, message:MidiMessage) -> MidiEvent {
+		MidiEvent { channel: u4::new(channel), message }
+	}
+	fn note_on(channel:u8, key:u8) -> MidiEvent {
+		event(channel, MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(127) })
+	}
+
+	fn synth() -> Synth {
+		Synth::with_sound_font(SAMPLE_RATE, Arc::new(SoundFont::new(&sound_font()).unwrap())).unwrap()
+	}
+
+	/// Render a second of the left channel, and count how many cycles it contains.
+	fn frequency(synth:&mut Synth) -> usize {
+		let mut buffer = vec![0.0; SAMPLE_RATE as usize * 2];
+		synth.render(&mut buffer);
+		buffer
+			.iter()
+			.step_by(2)
+			.collect::<Vec<_>>()
+			.windows(2)
+			.filter(|pair| *pair[0] <= 0.0 && *pair[1] > 0.0)
+			.count()
+	}
+
+#[test]
+pub fn invalid_data() {
+	assert!(matches!(SoundFont::new(b"RIFF\x04\x00\x00\x00sfbk"), Err(crate::Error::Synth(SynthError::InvalidSoundFont))));
+	assert!(matches!(SoundFont::new(&sound_font()[..200]), Err(crate::Error::Synth(SynthError::InvalidSoundFont))));
+}
+
+#[test]
+pub fn presets() {
+	let sound_font = SoundFont::new(&sound_font()).unwrap();
+	assert!(sound_font.has_preset(0, 0));
+	assert!(sound_font.has_preset(1, 0));
+	assert!(sound_font.has_preset(128, 0));
+	assert!(!sound_font.has_preset(0, 1));
+}
+
+#[test]
+pub fn pitch() {
+	let mut synth = synth();
+	synth.receive(&note_on(0, 60));
+	assert!(frequency(&mut synth).abs_diff(100) <= 1);
+
+	let mut synth = self::synth();
+	synth.receive(&note_on(0, 72));
+	assert!(frequency(&mut synth).abs_diff(200) <= 1);
+
+	// (a full bend up raises the pitch by two semitones)
+	let mut synth = self::synth();
+	synth.receive(&event(0, MidiMessage::PitchBend { bend: midly::PitchBend(midly::num::u14::new(0x3FFF)) }));
+	synth.receive(&note_on(0, 60));
+	assert!(frequency(&mut synth).abs_diff(112) <= 1);
+}
+
+#[test]
+pub fn bank_select() {
+	let mut synth = synth();
+	synth.receive(&event(0, MidiMessage::Controller { controller: u7::new(0), value: u7::new(1) }));
+	synth.receive(&note_on(0, 60));
+	assert!(frequency(&mut synth).abs_diff(200) <= 1);
+
+	// (a bank without the program falls back to the first bank)
+	let mut synth = self::synth();
+	synth.receive(&event(0, MidiMessage::Controller { controller: u7::new(0), value: u7::new(5) }));
+	synth.receive(&note_on(0, 60));
+	assert!(frequency(&mut synth).abs_diff(100) <= 1);
+}
+
+#[test]
+pub fn percussion_plays_once() {
+	let mut synth = synth();
+	synth.receive(&note_on(9, 60));
+	assert!(frequency(&mut synth).abs_diff(10) <= 1);
+	assert!(synth.is_silent());
+}
+
+#[test]
+pub fn pan() {
+	let mut synth = synth();
+	synth.receive(&event(0, MidiMessage::Controller { controller: u7::new(10), value: u7::new(0) }));
+	synth.receive(&note_on(0, 60));
+
+	let mut buffer = vec![0.0; 2_000];
+	synth.render(&mut buffer);
+	assert!(buffer.iter().step_by(2).any(|sample| sample.abs() > 0.05));
+	assert!(buffer.iter().skip(1).step_by(2).all(|sample| sample.abs() < 0.001));
+}
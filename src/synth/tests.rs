@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use midly::{num::{u4, u7}, MidiMessage};
+
+use super::{Error, Synth};
+use crate::{score::MidiEvent, Performer};
+
+static MID_FILE_DATA:&[u8] = include_bytes!("../../test_midi_files/scarborough_fair.mid");
+const SAMPLE_RATE:u32 = 8_000;
+
+fn note_on(channel:u8, key:u8) -> MidiEvent {
+	MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(100) } }
+}
+fn note_off(channel:u8, key:u8) -> MidiEvent {
+	MidiEvent { channel: u4::new(channel), message: MidiMessage::NoteOff { key: u7::new(key), vel: u7::new(0) } }
+}
+fn controller(channel:u8, controller:u8, value:u8) -> MidiEvent {
+	MidiEvent { channel: u4::new(channel), message: MidiMessage::Controller { controller: u7::new(controller), value: u7::new(value) } }
+}
+
+fn peak(synth:&mut Synth, duration:Duration) -> f32 {
+	let mut buffer = vec![0.0; super::frames_in(duration, SAMPLE_RATE) * 2];
+	synth.render(&mut buffer);
+	buffer.iter().fold(0.0, |peak, sample| sample.abs().max(peak))
+}
+
+#[test]
+pub fn zero_sample_rate() {
+	assert!(matches!(Synth::new(0), Err(crate::Error::Synth(Error::InvalidSampleRate))));
+}
+
+#[test]
+pub fn notes_sound_until_released() {
+	let mut synth = Synth::new(SAMPLE_RATE).unwrap();
+	assert_eq!(peak(&mut synth, Duration::from_millis(100)), 0.0);
+
+	// (an organ holds its notes indefinitely)
+	synth.receive(&MidiEvent { channel: u4::new(0), message: MidiMessage::ProgramChange { program: u7::new(16) } });
+	synth.receive(&note_on(0, 60));
+	assert!(peak(&mut synth, Duration::from_secs(1)) > 0.01);
+	assert!(peak(&mut synth, Duration::from_secs(1)) > 0.01);
+
+	synth.receive(&note_off(0, 60));
+	peak(&mut synth, Duration::from_secs(1));
+	assert!(synth.is_silent());
+}
+
+#[test]
+pub fn sustain_pedal_holds_notes() {
+	let mut synth = Synth::new(SAMPLE_RATE).unwrap();
+	synth.receive(&MidiEvent { channel: u4::new(0), message: MidiMessage::ProgramChange { program: u7::new(16) } });
+	synth.receive(&controller(0, 64, 127));
+	synth.receive(&note_on(0, 60));
+	synth.receive(&note_off(0, 60));
+	assert!(peak(&mut synth, Duration::from_secs(1)) > 0.01);
+
+	synth.receive(&controller(0, 64, 0));
+	peak(&mut synth, Duration::from_secs(1));
+	assert!(synth.is_silent());
+}
+
+#[test]
+pub fn drums_play_out_in_full() {
+	let mut synth = Synth::new(SAMPLE_RATE).unwrap();
+	synth.receive(&note_on(9, 36));
+	synth.receive(&note_off(9, 36));
+	assert!(peak(&mut synth, Duration::from_millis(100)) > 0.01);
+
+	peak(&mut synth, Duration::from_secs(1));
+	assert!(synth.is_silent());
+}
+
+#[test]
+pub fn render_to_wav() {
+	let performer = Performer::new(midly::Smf::parse(MID_FILE_DATA).unwrap());
+	let mut wav = vec![];
+	performer.render_to_wav(&mut wav, SAMPLE_RATE, None).unwrap();
+
+	assert_eq!(&wav[0..4], b"RIFF");
+	assert_eq!(&wav[8..16], b"WAVEfmt ");
+	assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), SAMPLE_RATE);
+	assert_eq!(&wav[36..40], b"data");
+
+	let data_length = u32::from_le_bytes(wav[40..44].try_into().unwrap()) as usize;
+	assert_eq!(wav.len(), 44 + data_length);
+
+	// (the audio lasts as long as the score, plus some of the tail)
+	let duration = performer.score().calculate_duration(1.0);
+	let frames = data_length / 4;
+	assert!(frames >= super::frames_in(duration, SAMPLE_RATE) - SAMPLE_RATE as usize);
+	assert!(frames <= super::frames_in(duration + super::MAXIMUM_TAIL_DURATION, SAMPLE_RATE));
+	assert!(wav[44..].iter().any(|byte| *byte != 0));
+}
+
+#[test]
+pub fn wav_too_large() {
+	// (a byte rate beyond 32 bits can't be described, so nothing is written)
+	let mut wav = vec![];
+	assert!(matches!(super::wav::write(&mut wav, u32::MAX / 2, &[0.0, 0.0]), Err(Error::TooLarge)));
+	assert!(wav.is_empty());
+}
+
+#[test]
+pub fn render_to_wav_with_maximum_duration() {
+	let mut performer = Performer::new(midly::Smf::parse(MID_FILE_DATA).unwrap());
+	performer.set_looping(true).unwrap();
+	assert!(matches!(performer.render_to_wav(vec![], SAMPLE_RATE, None), Err(crate::Error::Synth(Error::Endless))));
+
+	let mut wav = vec![];
+	performer.render_to_wav(&mut wav, SAMPLE_RATE, Some(Duration::from_secs(3))).unwrap();
+	assert_eq!(wav.len(), 44 + 3 * SAMPLE_RATE as usize * 4);
+
+	let mut performer = Performer::new(midly::Smf::parse(MID_FILE_DATA).unwrap());
+	performer.set_loop_region(0, performer.get_length_in_ticks() / 2, None).unwrap();
+	assert!(matches!(performer.render_to_wav(vec![], SAMPLE_RATE, None), Err(crate::Error::Synth(Error::Endless))));
+
+	let mut wav = vec![];
+	performer.render_to_wav(&mut wav, SAMPLE_RATE, Some(Duration::from_secs(3))).unwrap();
+	assert_eq!(wav.len(), 44 + 3 * SAMPLE_RATE as usize * 4);
+}
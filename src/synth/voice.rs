@@ -0,0 +1,169 @@
+use super::patch::{Envelope, Patch, Waveform};
+#[cfg(feature = "soundfont")]
+use super::sound_font::SamplePlayer;
+
+/// A single sounding note.
+#[derive(Debug, Clone)]
+pub struct Voice {
+	pub channel: u8,
+	pub key: u8,
+	source: Source,
+	envelope: Envelope,
+	gain: f32,
+	/// The position of the note in the stereo field (from -1 for the left to 1 for the right), before the pan of its
+	/// channel is applied.
+	pub pan: f32,
+	/// Whether the note ignores its release and always plays out in full (as the built-in drums do).
+	one_shot: bool,
+
+	/// The time (in seconds) since the note began.
+	time: f32,
+	/// The time the note was released, and the envelope's level at that moment.
+	released: Option<(f32, f32)>,
+	/// Whether the note's key has been let go while the sustain pedal is down.
+	pub sustained: bool,
+}
+
+/// Where the sound of a note comes from.
+#[derive(Debug, Clone)]
+enum Source {
+	Oscillator(Oscillator),
+	#[cfg(feature = "soundfont")]
+	Sample(SamplePlayer),
+}
+
+#[derive(Debug, Clone)]
+struct Oscillator {
+	waveform: Waveform,
+	/// The frequency of the key (in Hz), before any pitch bend.
+	frequency: f32,
+	/// For drums, the frequency to sweep from and to over the given duration, regardless of pitch bend.
+	sweep: Option<(f32, f32, f32)>,
+	phase: f32,
+	noise_state: u32,
+}
+
+impl Voice {
+	pub fn new(channel:u8, key:u8, velocity:u8, patch:Patch) -> Voice {
+		Voice {
+			channel,
+			key,
+			source: Source::Oscillator(
+				Oscillator {
+					waveform: patch.waveform,
+					frequency: 440.0 * ((f32::from(key) - 69.0) / 12.0).exp2(),
+					sweep: patch.fixed_frequency.map(|(start, end)| (start, end, patch.envelope.decay)),
+					phase: 0.0,
+					noise_state: 0x9E37_79B9 ^ (u32::from(key) << 8 | u32::from(channel)),
+				}
+			),
+			envelope: patch.envelope,
+			gain: velocity_gain(velocity) * patch.gain,
+			pan: 0.0,
+			one_shot: patch.fixed_frequency.is_some(),
+
+			time: 0.0,
+			released: None,
+			sustained: false,
+		}
+	}
+
+	#[cfg(feature = "soundfont")]
+	pub fn from_sample(channel:u8, key:u8, velocity:u8, sample_player:SamplePlayer, envelope:Envelope, gain:f32, pan:f32) -> Voice {
+		Voice {
+			channel,
+			key,
+			source: Source::Sample(sample_player),
+			envelope,
+			gain: velocity_gain(velocity) * gain,
+			pan,
+			one_shot: false,
+
+			time: 0.0,
+			released: None,
+			sustained: false,
+		}
+	}
+}
+
+impl Voice {
+	/// Begin the release of the note (unless it is a one-shot, which always plays out in full).
+	pub fn release(&mut self) {
+		if self.released.is_none() && !self.one_shot {
+			self.released = Some((self.time, self.level()));
+		}
+	}
+
+	pub fn is_released(&self) -> bool {
+		self.released.is_some()
+	}
+
+	/// Whether the note has faded away completely.
+	pub fn is_finished(&self) -> bool {
+		#[cfg(feature = "soundfont")]
+		if let Source::Sample(sample_player) = &self.source {
+			if sample_player.is_finished() {
+				return true;
+			}
+		}
+
+		match self.released {
+			Some((released_at, _)) => self.time >= released_at + self.envelope.release,
+			None => self.envelope.sustain == 0.0 && self.time >= self.envelope.settled_at(),
+		}
+	}
+
+	fn level(&self) -> f32 {
+		match self.released {
+			Some((released_at, level_at_release)) => {
+				level_at_release * (1.0 - (self.time - released_at) / self.envelope.release).max(0.0)
+			},
+			None => self.envelope.level_while_held(self.time),
+		}
+	}
+
+	/// Produce the next sample of the note, with the given pitch bend (in semitones), then move on by one sample.
+	pub fn next_sample(&mut self, sample_duration:f32, pitch_bend:f32) -> f32 {
+		let sample = match &mut self.source {
+			Source::Oscillator(oscillator) => oscillator.next_sample(sample_duration, pitch_bend, self.time),
+			#[cfg(feature = "soundfont")]
+			Source::Sample(sample_player) => sample_player.next_sample(pitch_bend, self.released.is_none()),
+		};
+		let sample = sample * self.level() * self.gain;
+
+		self.time += sample_duration;
+
+		sample
+	}
+}
+
+impl Oscillator {
+	fn next_sample(&mut self, sample_duration:f32, pitch_bend:f32, time:f32) -> f32 {
+		let frequency = match self.sweep {
+			Some((start_frequency, end_frequency, duration)) => {
+				let progress = (time / duration).min(1.0);
+				start_frequency + (end_frequency - start_frequency) * progress
+			},
+			None => self.frequency * (pitch_bend / 12.0).exp2(),
+		};
+
+		let noise = if self.waveform == Waveform::Noise {
+			self.noise_state ^= self.noise_state << 13;
+			self.noise_state ^= self.noise_state >> 17;
+			self.noise_state ^= self.noise_state << 5;
+			(self.noise_state as f32 / u32::MAX as f32) * 2.0 - 1.0
+		} else {
+			0.0
+		};
+
+		let sample = self.waveform.sample(self.phase, noise);
+		self.phase = (self.phase + frequency * sample_duration).fract();
+
+		sample
+	}
+}
+
+fn velocity_gain(velocity:u8) -> f32 {
+	let velocity = f32::from(velocity) / 127.0;
+	velocity * velocity
+}
@@ -0,0 +1,50 @@
+use std::io::Write;
+
+use super::Error;
+
+const CHANNELS:u16 = 2;
+const BITS_PER_SAMPLE:u16 = 16;
+const BLOCK_ALIGN:u16 = CHANNELS * BITS_PER_SAMPLE / 8;
+
+/// Write interleaved stereo samples (from -1 to 1) out as a 16-bit PCM WAV file.
+///
+/// Fails with [`Error::TooLarge`] (before anything is written) if the sizes in the header don't fit in 32 bits.
+pub fn write(mut writer:impl Write, sample_rate:u32, samples:&[f32]) -> Result<(), Error> {
+	let data_length = samples
+		.len()
+		.checked_mul(usize::from(BITS_PER_SAMPLE / 8))
+		.and_then(|data_length| u32::try_from(data_length).ok())
+		.ok_or(Error::TooLarge)?;
+	let riff_length = data_length.checked_add(36).ok_or(Error::TooLarge)?;
+	let byte_rate = sample_rate.checked_mul(u32::from(BLOCK_ALIGN)).ok_or(Error::TooLarge)?;
+
+	write_chunks(&mut writer, riff_length, sample_rate, byte_rate, data_length, samples).map_err(Error::Io)
+}
+
+fn write_chunks(writer:&mut impl Write, riff_length:u32, sample_rate:u32, byte_rate:u32, data_length:u32, samples:&[f32]) -> std::io::Result<()> {
+	//header
+		writer.write_all(b"RIFF")?;
+		writer.write_all(&riff_length.to_le_bytes())?;
+		writer.write_all(b"WAVE")?;
+
+	//format chunk
+		writer.write_all(b"fmt ")?;
+		writer.write_all(&16_u32.to_le_bytes())?;
+		writer.write_all(&1_u16.to_le_bytes())?;
+		writer.write_all(&CHANNELS.to_le_bytes())?;
+		writer.write_all(&sample_rate.to_le_bytes())?;
+		writer.write_all(&byte_rate.to_le_bytes())?;
+		writer.write_all(&BLOCK_ALIGN.to_le_bytes())?;
+		writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+	//data chunk
+		writer.write_all(b"data")?;
+		writer.write_all(&data_length.to_le_bytes())?;
+		let data:Vec<u8> = samples
+			.iter()
+			.flat_map(|sample| ((sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16).to_le_bytes())
+			.collect();
+		writer.write_all(&data)?;
+
+	writer.flush()
+}
@@ -0,0 +1,101 @@
+mod poll_events {
+	use std::time::Instant;
+
+	use midly::{num::{u15, u24, u28, u4, u7}, Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+
+	use crate::{clock::VirtualClock, messages::ToConsole, sink::ChannelSink, Event, EventKinds, MetaEvent, MidiEvent, Performer};
+
+	static SYSEX:&[u8] = &[0x7E, 0x7F, 0x09, 0x01, 0xF7];
+	static ESCAPE:&[u8] = &[0xF8];
+
+	fn event(delta:u32, kind:TrackEventKind<'static>) -> TrackEvent<'static> {
+		TrackEvent { delta: u28::new(delta), kind }
+	}
+	fn note_on(key:u8, vel:u8) -> TrackEventKind<'static> {
+		TrackEventKind::Midi { channel: u4::new(0), message: MidiMessage::NoteOn { key: u7::new(key), vel: u7::new(vel) } }
+	}
+
+	/// A short score with every kind of event: meta, system exclusive, escape and channel messages.
+	fn smf() -> Smf<'static> {
+		Smf {
+			header: Header::new(Format::SingleTrack, Timing::Metrical(u15::new(96))),
+			tracks: vec![
+				vec![
+					event(0, TrackEventKind::Meta(MetaMessage::Tempo(u24::new(500_000)))),
+					event(0, TrackEventKind::Meta(MetaMessage::Text(b"start"))),
+					event(0, TrackEventKind::SysEx(SYSEX)),
+					event(0, note_on(60, 100)),
+					event(12, TrackEventKind::Escape(ESCAPE)),
+					event(12, note_on(60, 0)),
+					event(0, TrackEventKind::Meta(MetaMessage::EndOfTrack)),
+				]
+			]
+		}
+	}
+
+	/// Play the score through to the end against a virtual clock (so without waiting for it in real time), collecting
+	/// every event of the selected kinds.
+	fn perform(event_kinds:EventKinds) -> Vec<(usize, Event)> {
+		let (channel_to_console, channel_from_engine) = crossbeam_channel::unbounded::<ToConsole>();
+		let sink = ChannelSink::new(channel_to_console.clone());
+		let clock = Box::new(VirtualClock::new(Instant::now()));
+		let mut performer = Performer::try_new_with_channels(smf(), sink, channel_to_console, channel_from_engine, clock).unwrap();
+		performer.play().unwrap();
+
+		// (the engine reaches the end as soon as it has been told to play, leaving only its messages to be collected)
+		let mut events = vec![];
+		while performer.is_playing() {
+			events.extend(performer.poll_events(event_kinds).unwrap().unwrap());
+		}
+		events
+	}
+
+	#[test]
+	pub fn every_kind_reaches_the_consumer() {
+		let events = perform(EventKinds::all());
+
+		assert!(events.contains(&(0, Event::Meta(MetaEvent::Text(b"start".to_vec())))));
+		assert!(events.contains(&(0, Event::SysEx(SYSEX.to_vec()))));
+		assert!(events.contains(&(0, Event::Escape(ESCAPE.to_vec()))));
+		assert!(events.contains(&(0, Event::Midi(MidiEvent { channel: u4::new(0), message: MidiMessage::NoteOn { key: u7::new(60), vel: u7::new(100) } }))));
+	}
+
+	#[test]
+	pub fn excluded_kinds_are_dropped() {
+		let all = perform(EventKinds::all());
+
+		for event_kinds in [
+			EventKinds::midi_only(),
+			EventKinds { sysex: false, ..EventKinds::all() },
+			EventKinds { meta: false, escape: false, ..EventKinds::all() },
+			EventKinds { midi: false, sysex: false, escape: false, meta: true },
+		] {
+			let expected:Vec<(usize, Event)> = all.iter().filter(|(_, event)| event_kinds.includes(event)).cloned().collect();
+			assert!(!expected.is_empty());
+			assert_eq!(perform(event_kinds), expected);
+		}
+	}
+}
+
+mod tempo {
+	use crate::{Error, Performer};
+
+	static MID_FILE_DATA:&[u8] = include_bytes!("../test_midi_files/constant_tempo.mid");
+	static TIMECODE_MID_FILE_DATA:&[u8] = include_bytes!("../test_midi_files/timecode.mid");
+
+	#[test]
+	pub fn metrical() {
+		let performer = Performer::new(midly::Smf::parse(MID_FILE_DATA).unwrap());
+
+		assert!(performer.get_current_microseconds_per_beat().is_ok());
+		assert!(performer.get_current_bpm().is_ok());
+	}
+
+	#[test]
+	pub fn timecode() {
+		let performer = Performer::new(midly::Smf::parse(TIMECODE_MID_FILE_DATA).unwrap());
+
+		assert!(matches!(performer.get_current_microseconds_per_beat(), Err(Error::NoTempo)));
+		assert!(matches!(performer.get_current_bpm(), Err(Error::NoTempo)));
+	}
+}
@@ -1,6 +1,6 @@
 use std::{ops::Mul, time::{Duration, Instant}};
 
-use midly::Timing;
+use midly::{Fps, Timing};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Timer {
@@ -9,6 +9,7 @@ pub struct Timer {
 	maximum_sleep_time: Duration,
 	number_of_ticks_that_would_fit_into_maximum_sleep_time: usize,
 	last_instant: Option<Instant>,
+	fixed_tempo: bool,
 	
 	speed: f32,
 }
@@ -25,32 +26,85 @@ impl Timer {
 			maximum_sleep_time,
 			number_of_ticks_that_would_fit_into_maximum_sleep_time: 0,
 			last_instant: None,
+			fixed_tempo: false,
 
 			speed: 1.0,
 		}
 	}
 
+	/// Create an instance of a [Timer] with the given SMPTE frame rate and ticks-per-frame.
+	///
+	/// The tick duration is fixed by the frame rate, so any tempo changes are ignored.
+	pub fn new_with_frames_per_second(frames_per_second:Fps, ticks_per_frame:u8, maximum_sleep_time:Duration) -> Timer {
+		let mut timer = Timer {
+			ticks_per_beat: 0,
+			tick_duration: Duration::ZERO,
+			maximum_sleep_time,
+			number_of_ticks_that_would_fit_into_maximum_sleep_time: 0,
+			last_instant: None,
+			fixed_tempo: true,
+
+			speed: 1.0,
+		};
+		timer.set_tick_duration(Duration::from_secs_f64(1.0 / (f64::from(frames_per_second.as_f32()) * f64::from(ticks_per_frame))));
+		timer
+	}
+
 	/// Create an instance of a [Timer] with the given [Timing].
 	///
-	/// Initially the tempo will be set to infinity. This is rarely an issue as a tempo change
-	/// message will set it, which is usual found in the first tick of a score.
+	/// For [`Timing::Metrical`] values, the tempo will initially be set to infinity. This is rarely
+	/// an issue as a tempo change message will set it, which is usual found in the first tick of a score.
+	///
+	/// # Errors
+	/// Will return an error if the [Timing] value describes zero ticks per beat or per frame.
 	pub fn new(timing:Timing, maximum_sleep_time:Duration) -> Result<Timer, ()> {
-		let ticks_per_beat = match timing {
-			Timing::Metrical(n) => u16::from(n),
-			Timing::Timecode(_frames_per_second, _ticks_per_frame) => {
-				return Err(())
+		match timing {
+			Timing::Metrical(ticks_per_beat) => {
+				if u16::from(ticks_per_beat) == 0 {
+					return Err(());
+				}
+				Ok(Timer::new_with_ticks_per_beat(u16::from(ticks_per_beat), maximum_sleep_time))
 			},
-		};
-
-		Ok(Timer::new_with_ticks_per_beat(ticks_per_beat, maximum_sleep_time))
+			Timing::Timecode(frames_per_second, ticks_per_frame) => {
+				if ticks_per_frame == 0 {
+					return Err(());
+				}
+				Ok(Timer::new_with_frames_per_second(frames_per_second, ticks_per_frame, maximum_sleep_time))
+			},
+		}
 	}	
 }
 
 impl Timer {
-	pub fn change_tempo(&mut self, tempo:u32) {
-		self.tick_duration = Duration::from_micros(u64::from(tempo / u32::from(self.ticks_per_beat)));
+	/// Switch this timer over to the given [Timing], keeping its speed and its place in time, so that the
+	/// ticks of a following score can be timed on without a gap.
+	///
+	/// # Errors
+	/// Will return an error if the [Timing] value describes zero ticks per beat or per frame.
+	pub fn change_timing(&mut self, timing:Timing) -> Result<(), ()> {
+		*self = Timer {
+			last_instant: self.last_instant,
+			speed: self.speed,
+			..Timer::new(timing, self.maximum_sleep_time)?
+		};
+		Ok(())
+	}
+
+	fn set_tick_duration(&mut self, tick_duration:Duration) {
+		self.tick_duration = tick_duration;
 		self.number_of_ticks_that_would_fit_into_maximum_sleep_time = self.maximum_sleep_time.div_duration_f32(self.tick_duration).trunc() as usize;
 	}
+
+	/// Change the tempo (in microseconds per beat) of this timer.
+	///
+	/// Timers using SMPTE timecode have a fixed tick duration, and so ignore tempo changes.
+	pub fn change_tempo(&mut self, tempo:u32) {
+		if self.fixed_tempo {
+			return;
+		}
+
+		self.set_tick_duration(Duration::from_micros(u64::from(tempo / u32::from(self.ticks_per_beat))));
+	}
 }
 
 impl Timer {
@@ -66,23 +120,47 @@ impl Timer {
 		self.tick_duration.mul(u32::try_from(ticks).unwrap_or(u32::MAX)).div_f32(self.speed)
 	}
 
-	pub fn calculate_sleeping_time(&mut self, ticks:usize) -> Duration {
-		let mut duration = self.calculate_duration_of_ticks(ticks);
+	/// Get the instant at which the ticks given to the last call of [`Timer::schedule`] will have elapsed.
+	pub fn get_last_instant(&self) -> Option<Instant> {
+		self.last_instant
+	}
 
-		match self.last_instant {
-			Some(last_instant) => {
-				self.last_instant = Some(last_instant + duration);
-				duration = duration.checked_sub(last_instant.elapsed()).unwrap_or(duration);
-			}
-			None => self.last_instant = Some(Instant::now() + duration),
-		}
+	/// Get the instant at which the given number of ticks will have elapsed, counting on from the ticks given to
+	/// the last call (or from `now`, if the timer has just been created or reset).
+	pub fn schedule(&mut self, ticks:usize, now:Instant) -> Instant {
+		let start = self.last_instant.unwrap_or(now);
+		// (a speed of zero never ends, and so is represented by the furthest representable instant)
+		let instant = start.checked_add(self.calculate_duration_of_ticks(ticks)).unwrap_or_else(|| {
+			start + Duration::from_secs(u64::from(u32::MAX))
+		});
+
+		self.last_instant = Some(instant);
+		instant
+	}
 
-		duration
+	/// Count the next ticks on from the given instant, rather than from the last scheduled ticks.
+	pub fn restart_at(&mut self, instant:Instant) {
+		self.last_instant = Some(instant);
 	}
 }
 
 impl Timer {
+	pub fn get_speed(&self) -> f32 {
+		self.speed
+	}
 	pub fn set_speed(&mut self, speed:f32) {
 		self.speed = speed;
 	}
+
+	/// Set the speed of this timer, stretching or squeezing the time remaining from `now` until the last scheduled
+	/// ticks elapse.
+	pub fn change_speed(&mut self, speed:f32, now:Instant) {
+		if let Some(last_instant) = self.last_instant {
+			if last_instant > now && speed > 0.0 {
+				self.last_instant = Some(now + (last_instant - now).mul_f32(self.speed / speed));
+			}
+		}
+
+		self.speed = speed;
+	}
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use std::time::{Duration, Instant};
+
+use crate::score::Event;
+
+/// An [Event], along with the track number it is associated with and the [Timestamp] it was scheduled for.
+pub type TimestampedEvent = (usize, Event, Timestamp);
+
+/// When an event was scheduled to be heard.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+	/// The tick position of the event in the midi score.
+	pub tick: usize,
+	/// The moment the event is meant to be heard. (With a lookahead set, this will be later than the moment the event
+	/// is sent by the engine.)
+	pub instant: Instant,
+	/// The time from the start of the midi score to the event, at the playback speed in effect when it was sent.
+	pub time: Duration,
+}